@@ -0,0 +1,40 @@
+//! Times building and scoring the timeline of a 200 member board, where the
+//! per-star work shows. Run with `cargo bench`.
+#![warn(clippy::pedantic)]
+use aoc_timeline::{sample, scored, standings, timeline, Aoc};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// The members of 25 sample boards on one board, 200 in all.
+fn large_board() -> Aoc {
+    let mut aoc = sample::leaderboard(2023, sample::SEED);
+    aoc.members.clear();
+    for seed in 0..25 {
+        for mut member in sample::leaderboard(2023, seed).members.into_values() {
+            // Sample ids are 7919 apart, so these stay unique.
+            member.id += i32::try_from(seed).unwrap() * 100;
+            member.name = member.name.map(|name| format!("{name} {seed}"));
+            aoc.members.insert(member.id.to_string(), member);
+        }
+    }
+    aoc
+}
+
+/// Runs `run` for two seconds and prints the mean time per run.
+fn measure<T>(name: &str, mut run: impl FnMut() -> T) {
+    let start = Instant::now();
+    let mut runs = 0;
+    while start.elapsed() < Duration::from_secs(2) {
+        black_box(run());
+        runs += 1;
+    }
+    println!("{name:<10} {:>10.1?} per run", start.elapsed() / runs);
+}
+
+fn main() {
+    let aoc = large_board();
+    measure("timeline", || timeline(black_box(&aoc)));
+    measure("scored", || scored(black_box(&aoc)));
+    let events = scored(&aoc);
+    measure("standings", || standings(black_box(&events)));
+}
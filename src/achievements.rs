@@ -0,0 +1,198 @@
+//! Badges for memorable moments, each awarded by its own rule.
+use crate::layout;
+use crate::ranks::ranks;
+use crate::scoring::Counting;
+use crate::streaks::streak;
+use crate::{display_names, duration_string, timeline, Aoc, Report};
+use chrono::{DateTime, Timelike, Utc};
+use itertools::Itertools;
+use serde::Serialize;
+
+/// A badge earned by one member.
+#[derive(Debug, Serialize)]
+pub struct Badge {
+    pub badge: &'static str,
+    pub member: String,
+    /// What earned it, like `01-1 at 05:00:31`.
+    pub detail: String,
+}
+
+/// One kind of badge. Add a rule to [`rules`] to hand out a new badge.
+pub trait Achievement {
+    /// Shown on every badge the rule awards.
+    fn name(&self) -> &'static str;
+    /// Every badge earned on `aoc` by `now`, given its timeline.
+    fn award(&self, aoc: &Aoc, timeline: &[Report], now: DateTime<Utc>) -> Vec<Badge>;
+}
+
+/// Whoever got the first star of the event.
+pub struct FirstSolver;
+
+impl Achievement for FirstSolver {
+    fn name(&self) -> &'static str {
+        "First solver"
+    }
+
+    fn award(&self, _aoc: &Aoc, timeline: &[Report], _now: DateTime<Utc>) -> Vec<Badge> {
+        let Some(first) = timeline.first() else {
+            return Vec::new();
+        };
+        timeline
+            .iter()
+            .take_while(|event| event.timestamp == first.timestamp)
+            .map(|event| Badge {
+                badge: self.name(),
+                member: event.member.to_string(),
+                detail: format!("{} at {}", event.star, event.timestamp.format("%H:%M:%S")),
+            })
+            .collect()
+    }
+}
+
+/// The quickest part 2 after part 1 of the event.
+pub struct FastestDelta;
+
+impl Achievement for FastestDelta {
+    fn name(&self) -> &'static str {
+        "Fastest part 2"
+    }
+
+    fn award(&self, _aoc: &Aoc, timeline: &[Report], _now: DateTime<Utc>) -> Vec<Badge> {
+        timeline
+            .iter()
+            .filter_map(|event| Some((event.delta?, event)))
+            .min_set_by_key(|(delta, _)| *delta)
+            .into_iter()
+            .map(|(delta, event)| Badge {
+                badge: self.name(),
+                member: event.member.to_string(),
+                detail: format!("{} in {}", event.star, duration_string(delta)),
+            })
+            .collect()
+    }
+}
+
+/// A star between 02:00 and 05:00 local time.
+pub struct NightOwl;
+
+impl Achievement for NightOwl {
+    fn name(&self) -> &'static str {
+        "Night owl"
+    }
+
+    fn award(&self, _aoc: &Aoc, timeline: &[Report], _now: DateTime<Utc>) -> Vec<Badge> {
+        timeline
+            .iter()
+            .filter(|event| (2..5).contains(&event.timestamp.hour()))
+            .into_group_map_by(|event| event.member.clone())
+            .into_iter()
+            .sorted_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(member, events)| Badge {
+                badge: self.name(),
+                member: member.to_string(),
+                detail: format!(
+                    "{} star{} in the small hours",
+                    events.len(),
+                    if events.len() == 1 { "" } else { "s" }
+                ),
+            })
+            .collect()
+    }
+}
+
+/// The biggest climb up the board on each day, by the points of the report.
+pub struct Comeback<'a>(pub Counting<'a>);
+
+impl Achievement for Comeback<'_> {
+    fn name(&self) -> &'static str {
+        "Comeback of the day"
+    }
+
+    fn award(&self, aoc: &Aoc, _timeline: &[Report], _now: DateTime<Utc>) -> Vec<Badge> {
+        let rows = ranks(aoc, self.0);
+        rows.iter()
+            .flat_map(|row| {
+                row.ranks
+                    .iter()
+                    .tuple_windows()
+                    .enumerate()
+                    .filter_map(|(day, (from, to))| Some((day + 2, from.zip(*to)?)))
+                    .filter(|(_, (from, to))| to < from)
+                    .map(|(day, (from, to))| (day, from - to, row.member.as_str()))
+            })
+            .into_group_map_by(|(day, _, _)| *day)
+            .into_iter()
+            .sorted_by_key(|(day, _)| *day)
+            .flat_map(|(_, climbs)| climbs.into_iter().max_set_by_key(|(_, climb, _)| *climb))
+            .map(|(day, climb, member)| Badge {
+                badge: self.name(),
+                member: member.to_string(),
+                detail: format!(
+                    "up {climb} place{} on day {day}",
+                    if climb == 1 { "" } else { "s" }
+                ),
+            })
+            .collect()
+    }
+}
+
+/// Both stars on the day of release, seven days running.
+pub struct PerfectWeek;
+
+impl Achievement for PerfectWeek {
+    fn name(&self) -> &'static str {
+        "Perfect week"
+    }
+
+    fn award(&self, aoc: &Aoc, _timeline: &[Report], now: DateTime<Utc>) -> Vec<Badge> {
+        let names = display_names(&aoc.members);
+        aoc.members
+            .values()
+            .filter_map(|member| {
+                let (longest, _) = streak(member, aoc.year(), now);
+                (longest >= 7).then(|| Badge {
+                    badge: self.name(),
+                    member: names[&member.id].clone(),
+                    detail: format!("{longest} days in a row"),
+                })
+            })
+            .sorted_by(|a, b| a.member.cmp(&b.member))
+            .collect()
+    }
+}
+
+/// Every rule, in the order their badges are shown, with places on the
+/// board counted by `counting`.
+pub fn rules(counting: Counting) -> Vec<Box<dyn Achievement + '_>> {
+    vec![
+        Box::new(FirstSolver),
+        Box::new(FastestDelta),
+        Box::new(NightOwl),
+        Box::new(Comeback(counting)),
+        Box::new(PerfectWeek),
+    ]
+}
+
+/// Every badge earned on `aoc` by `now`.
+pub fn badges(aoc: &Aoc, now: DateTime<Utc>, counting: Counting) -> Vec<Badge> {
+    let timeline = timeline(aoc);
+    rules(counting)
+        .iter()
+        .flat_map(|rule| rule.award(aoc, &timeline, now))
+        .collect()
+}
+
+pub fn print(badges: &[Badge]) {
+    println!("\nBadges:");
+    if badges.is_empty() {
+        println!("  none yet");
+    }
+    for badge in badges {
+        println!(
+            "  {:22} {} {}",
+            badge.badge,
+            layout::name(&badge.member),
+            badge.detail
+        );
+    }
+}
@@ -0,0 +1,56 @@
+//! Made-up names in place of the real ones, for sharing reports publicly.
+use crate::{stable_hash, Aoc};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+const ADJECTIVES: [&str; 32] = [
+    "Amber", "Bold", "Brave", "Bright", "Calm", "Clever", "Cosmic", "Crimson", "Daring", "Eager",
+    "Fancy", "Frosty", "Gentle", "Golden", "Happy", "Jolly", "Keen", "Lucky", "Merry", "Mighty",
+    "Nimble", "Quiet", "Rapid", "Silver", "Sleepy", "Snowy", "Swift", "Tidy", "Velvet", "Witty",
+    "Young", "Zesty",
+];
+
+const ANIMALS: [&str; 32] = [
+    "Badger", "Beaver", "Bison", "Caribou", "Crane", "Dolphin", "Elk", "Ermine", "Falcon",
+    "Ferret", "Fox", "Hare", "Hedgehog", "Heron", "Lynx", "Marmot", "Moose", "Narwhal", "Otter",
+    "Owl", "Penguin", "Puffin", "Raven", "Reindeer", "Robin", "Seal", "Squirrel", "Stoat", "Swan",
+    "Walrus", "Wolf", "Yak",
+];
+
+/// A name like `Swift Otter` picked by hashing `id`, so a member gets the
+/// same one every run and on every board.
+pub fn pseudonym(id: i32) -> String {
+    let hash = stable_hash(id.to_le_bytes());
+    let adjective = ADJECTIVES[usize::try_from(hash % 32).unwrap()];
+    let animal = ANIMALS[usize::try_from((hash >> 32) % 32).unwrap()];
+    format!("{adjective} {animal}")
+}
+
+/// Renames every member of `boards`, anonymous ones included, after
+/// [`pseudonym`]. Members whose pseudonyms clash are numbered in id order,
+/// rather than told apart by id as real names are.
+pub fn apply(boards: &mut [Aoc]) {
+    let ids: BTreeSet<i32> = boards
+        .iter()
+        .flat_map(|board| board.members.values().map(|member| member.id))
+        .collect();
+    let mut taken = HashSet::new();
+    let names: HashMap<i32, String> = ids
+        .into_iter()
+        .map(|id| {
+            let base = pseudonym(id);
+            let mut name = base.clone();
+            let mut n = 1;
+            while taken.contains(&name) {
+                n += 1;
+                name = format!("{base} {n}");
+            }
+            taken.insert(name.clone());
+            (id, name)
+        })
+        .collect();
+    for board in boards {
+        for member in board.members.values_mut() {
+            member.name = Some(names[&member.id].clone());
+        }
+    }
+}
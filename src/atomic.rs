@@ -0,0 +1,90 @@
+//! Files that several runs share, like the cache from cron and a manual run
+//! at once, written whole or not at all.
+use crate::error::{Error, Result};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+fn write_error(path: &Path) -> impl Fn(std::io::Error) -> Error + '_ {
+    |source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// `path` with `extension` added to its file name.
+fn beside(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+fn create_parent(path: &Path) -> Result<()> {
+    match path.parent() {
+        Some(dir) => std::fs::create_dir_all(dir).map_err(write_error(path)),
+        None => Ok(()),
+    }
+}
+
+/// Replaces `path` with `contents` through a temporary file next to it, so a
+/// crash leaves either the old or the new file, creating the directory if
+/// needed.
+///
+/// # Errors
+///
+/// When the directory or the file cannot be written.
+pub fn write(path: &Path, contents: &[u8]) -> Result<()> {
+    replace(path, |temporary| {
+        let mut file = File::create(temporary)?;
+        file.write_all(contents)?;
+        file.sync_all()
+    })
+}
+
+/// [`write`] for writers that want a path, which `fill` is given to write
+/// the temporary file to.
+///
+/// # Errors
+///
+/// When the directory cannot be created or `fill` or the rename fails.
+pub fn replace(path: &Path, fill: impl FnOnce(&Path) -> std::io::Result<()>) -> Result<()> {
+    create_parent(path)?;
+    let temporary = beside(path, &format!(".{}.tmp", std::process::id()));
+    let written = fill(&temporary).and_then(|()| std::fs::rename(&temporary, path));
+    if written.is_err() {
+        let _ = std::fs::remove_file(&temporary);
+    }
+    written.map_err(write_error(path))
+}
+
+/// Held while a run reads, changes and writes a shared file.
+pub struct Lock {
+    _file: File,
+}
+
+/// Waits until no other run is changing `path`, then keeps others waiting
+/// until the lock is dropped. The lock is `path` with `.lock` added.
+///
+/// # Errors
+///
+/// When the lock file cannot be created or locked.
+pub fn lock(path: &Path) -> Result<Lock> {
+    let path = beside(path, ".lock");
+    create_parent(&path)?;
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .map_err(write_error(&path))?;
+    match file.try_lock() {
+        Ok(()) => {}
+        Err(TryLockError::WouldBlock) => {
+            info!("waiting for another run to finish with {}", path.display());
+            file.lock().map_err(write_error(&path))?;
+        }
+        Err(TryLockError::Error(e)) => return Err(write_error(&path)(e)),
+    }
+    Ok(Lock { _file: file })
+}
@@ -0,0 +1,186 @@
+//! Season-over-season comparison against an earlier year's leaderboard.
+use crate::layout;
+use crate::{
+    automatic, count_elapsed, display_names, duration_string, timeline, Aoc, ElapsedFrom, Member,
+    Report,
+};
+use chrono::Duration;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+pub struct Delta {
+    pub member: String,
+    pub stars: i32,
+    pub medians: Medians,
+    /// `None` for members with no match in the baseline.
+    pub previous: Option<Previous>,
+}
+
+pub struct Previous {
+    pub name: String,
+    pub stars: i32,
+    pub medians: Medians,
+}
+
+/// A member's median solve times of each part, both counted from the puzzle
+/// unlock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Medians {
+    pub part1: Option<Duration>,
+    pub part2: Option<Duration>,
+}
+
+/// Parses `THIS_YEAR=LAST_YEAR` name pairs for members who renamed.
+///
+/// # Errors
+///
+/// When there is no `=` in `s`.
+pub fn parse_alias(s: &str) -> Result<(String, String), String> {
+    s.split_once('=')
+        .map(|(new, old)| (new.to_string(), old.to_string()))
+        .ok_or_else(|| format!("expected NEW=OLD, got '{s}'"))
+}
+
+fn median(events: &[&Report], part: u32) -> Option<Duration> {
+    let sorted = events
+        .iter()
+        .filter(|event| event.part() == part)
+        .map(|event| event.elapsed)
+        .sorted()
+        .collect_vec();
+    sorted.get(sorted.len() / 2).copied()
+}
+
+/// The medians of every member of `aoc` with a star, keyed by member id.
+fn median_elapsed(aoc: &Aoc) -> HashMap<i32, Medians> {
+    let mut events = timeline(aoc);
+    count_elapsed(&mut events, aoc.year(), ElapsedFrom::Unlock);
+    events
+        .iter()
+        .filter(|event| !automatic(event.day(), event.part()))
+        .into_group_map_by(|event| event.member_id)
+        .into_iter()
+        .map(|(id, events)| {
+            let medians = Medians {
+                part1: median(&events, 1),
+                part2: median(&events, 2),
+            };
+            (id, medians)
+        })
+        .collect()
+}
+
+/// Matches members of `current` to `baseline` by display name, or through
+/// `aliases` (current name to baseline name), since ids change every year.
+/// Names shared by more than one member of a board get an `#id` suffix.
+pub fn compare(current: &Aoc, baseline: &Aoc, aliases: &HashMap<String, String>) -> Vec<Delta> {
+    let medians = median_elapsed(current);
+    let old_medians = median_elapsed(baseline);
+    let names = display_names(&current.members);
+    let old_names = display_names(&baseline.members);
+    let old_members: HashMap<&str, &Member> = baseline
+        .members
+        .values()
+        .map(|m| (old_names[&m.id].as_str(), m))
+        .collect();
+    current
+        .members
+        .values()
+        .map(|member| {
+            let name = names[&member.id].clone();
+            let old_name = aliases.get(&name).unwrap_or(&name);
+            Delta {
+                medians: medians.get(&member.id).copied().unwrap_or_default(),
+                stars: member.stars,
+                previous: old_members.get(old_name.as_str()).map(|old| Previous {
+                    name: old_name.clone(),
+                    stars: old.stars,
+                    medians: old_medians.get(&old.id).copied().unwrap_or_default(),
+                }),
+                member: name,
+            }
+        })
+        .sorted_by(|a, b| b.stars.cmp(&a.stars).then_with(|| a.member.cmp(&b.member)))
+        .collect()
+}
+
+fn signed(d: Duration) -> String {
+    if d < Duration::zero() {
+        format!("-{}", duration_string(-d))
+    } else {
+        format!("+{}", duration_string(d))
+    }
+}
+
+pub fn print(deltas: &[Delta], baseline: &Aoc) {
+    println!("\nCompared with {}:", baseline.event);
+    for delta in deltas {
+        let Some(previous) = &delta.previous else {
+            println!(
+                "  {} {:2}*  new this year",
+                layout::name(&delta.member),
+                delta.stars
+            );
+            continue;
+        };
+        let median = [
+            ("part 1", delta.medians.part1, previous.medians.part1),
+            ("part 2", delta.medians.part2, previous.medians.part2),
+        ]
+        .into_iter()
+        .filter_map(|(part, now, then)| match (now, then) {
+            (Some(now), Some(then)) => Some(format!(
+                "{part} median {} ({})",
+                duration_string(now),
+                signed(now - then)
+            )),
+            (Some(now), None) => Some(format!("{part} median {}", duration_string(now))),
+            _ => None,
+        })
+        .join(", ");
+        let renamed = if previous.name == delta.member {
+            String::new()
+        } else {
+            format!("  (was {})", previous.name)
+        };
+        println!(
+            "  {} {:2}* ({:+})  {median}{renamed}",
+            layout::name(&delta.member),
+            delta.stars,
+            delta.stars - previous.stars
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample;
+
+    #[test]
+    fn members_sharing_a_name_keep_their_baseline() {
+        let mut aoc = sample::leaderboard(2023, sample::SEED);
+        for member in aoc.members.values_mut() {
+            if member.name.as_deref() == Some("Bob") {
+                member.name = Some("Alice".into());
+            }
+        }
+        let deltas = compare(&aoc, &aoc, &HashMap::new());
+        assert_eq!(
+            deltas
+                .iter()
+                .filter(|d| d.member.starts_with("Alice#"))
+                .count(),
+            2
+        );
+        for delta in deltas {
+            let previous = delta.previous.expect("every member is in the baseline");
+            assert_eq!(previous.stars, delta.stars);
+            assert_eq!(previous.medians.part1, delta.medians.part1);
+            assert_eq!(previous.medians.part2, delta.medians.part2);
+            if delta.stars > 0 {
+                assert!(delta.medians.part1.is_some());
+            }
+        }
+    }
+}
@@ -0,0 +1,291 @@
+//! Leaderboards fetched from adventofcode.com, kept on disk between runs in
+//! one file with what is needed to ask whether they changed since.
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::warnings::{Category, Warnings};
+use crate::{atomic, Aoc, Board};
+use chrono::{DateTime, Local};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use itertools::Itertools;
+use securestore::{KeySource, SecretsManager};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use tracing::debug;
+
+#[derive(Deserialize, Serialize)]
+pub struct CacheEntry {
+    pub timestamp: DateTime<Local>,
+    pub data: Aoc,
+    #[serde(flatten)]
+    pub validators: Validators,
+}
+
+/// What adventofcode.com said about the version it sent, so the next request
+/// can ask for the leaderboard only if it changed since.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct Validators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+/// Keyed by `year/leaderboard`.
+pub type Cache = HashMap<String, CacheEntry>;
+
+/// The layout of the cache file, raised whenever it changes. Version 1 was
+/// the bare [`Cache`], without the version, at first keyed by leaderboard id
+/// alone.
+const CACHE_VERSION: u64 = 2;
+
+/// The cache file.
+#[derive(Deserialize, Serialize)]
+struct CacheFile<T> {
+    version: u64,
+    boards: T,
+}
+
+/// The name of the one secret in an encrypted cache.
+const CACHE_SECRET: &str = "cache";
+
+/// The first bytes of gzip data, which JSON never starts with.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Reads the cache, decompressing, decrypting and migrating it as needed. A
+/// cache that cannot be parsed or decrypted, or was written by a newer
+/// version, is set aside as `<cache>.corrupt` with a warning in `warnings`,
+/// and everything is fetched again.
+///
+/// # Errors
+///
+/// When the cache file exists but cannot be read.
+pub fn read(config: &Config, warnings: &mut Warnings) -> Result<Cache> {
+    if !config.cache.exists() {
+        return Ok(Cache::new());
+    }
+    let bytes = std::fs::read(&config.cache).map_err(|source| Error::CacheRead {
+        path: config.cache.clone(),
+        source,
+    })?;
+    match unpack(config, bytes).and_then(parse) {
+        Ok(cache) => Ok(cache),
+        Err(problem) => {
+            let mut aside = config.cache.clone().into_os_string();
+            aside.push(".corrupt");
+            warnings.push(
+                Category::Cache,
+                format!(
+                    "{} {problem}, fetching again; the old cache is kept as {}",
+                    config.cache.display(),
+                    Path::new(&aside).display()
+                ),
+            );
+            let _ = std::fs::rename(&config.cache, &aside);
+            Ok(Cache::new())
+        }
+    }
+}
+
+/// The JSON in the cache file's `bytes`, out of its securestore vault if it
+/// is encrypted and decompressed if it is compressed.
+fn unpack(config: &Config, bytes: Vec<u8>) -> std::result::Result<serde_json::Value, String> {
+    let value: serde_json::Value =
+        serde_json::from_slice(&inflate(bytes)?).map_err(|e| format!("is not JSON ({e})"))?;
+    if value.get("secrets").is_none() || value.get("iv").is_none() {
+        return Ok(value);
+    }
+    if !config.encrypt_cache {
+        return Err("is encrypted but encrypt_cache is off".into());
+    }
+    let bytes = SecretsManager::load(&config.cache, KeySource::Path(&config.secrets_key))
+        .and_then(|vault| vault.get_as::<Vec<u8>>(CACHE_SECRET))
+        .map_err(|e| format!("cannot be decrypted ({e})"))?;
+    serde_json::from_slice(&inflate(bytes)?).map_err(|e| format!("is not JSON inside ({e})"))
+}
+
+/// `bytes` decompressed if they are gzip, or as they are.
+fn inflate(bytes: Vec<u8>) -> std::result::Result<Vec<u8>, String> {
+    if !bytes.starts_with(&GZIP_MAGIC) {
+        return Ok(bytes);
+    }
+    let mut inflated = Vec::with_capacity(bytes.len() * 8);
+    GzDecoder::new(bytes.as_slice())
+        .read_to_end(&mut inflated)
+        .map_err(|e| format!("cannot be decompressed ({e})"))?;
+    Ok(inflated)
+}
+
+fn deflate(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    // Writing to a Vec cannot fail.
+    encoder.write_all(bytes).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// `cache` with the bare leaderboard ids the first versions used as keys
+/// replaced by [`Board::cache_key`], taking the year from each entry.
+fn rekey(cache: Cache) -> Cache {
+    cache
+        .into_iter()
+        .map(|(key, entry)| match key.parse() {
+            Ok(leaderboard) => {
+                let board = Board {
+                    year: entry.data.year(),
+                    leaderboard,
+                };
+                (board.cache_key(), entry)
+            }
+            Err(_) => (key, entry),
+        })
+        .collect()
+}
+
+/// The boards in the cache file's `value`, from any layout this version
+/// knows.
+fn parse(value: serde_json::Value) -> std::result::Result<Cache, String> {
+    let Some(version) = value.get("version") else {
+        debug!("reading a version 1 cache, the next write upgrades it");
+        return serde_json::from_value(value)
+            .map(rekey)
+            .map_err(|e| format!("is not a version 1 cache ({e})"));
+    };
+    match version.as_u64() {
+        Some(CACHE_VERSION) => serde_json::from_value::<CacheFile<Cache>>(value)
+            .map(|file| file.boards)
+            .map_err(|e| format!("is not a version {CACHE_VERSION} cache ({e})")),
+        _ => Err(format!(
+            "has version {version}, which this version cannot read"
+        )),
+    }
+}
+
+/// Saves `cache` in the current layout, compressed and encrypted if the
+/// config says so.
+///
+/// # Errors
+///
+/// When there is no key to encrypt with or the file cannot be written.
+pub fn write(config: &Config, cache: &Cache) -> Result<()> {
+    let file = CacheFile {
+        version: CACHE_VERSION,
+        boards: cache,
+    };
+    let mut bytes = serde_json::to_vec(&file).unwrap();
+    if config.compress_cache {
+        bytes = deflate(&bytes);
+    }
+    if !config.encrypt_cache {
+        return atomic::write(&config.cache, &bytes);
+    }
+    let mut vault =
+        SecretsManager::new(KeySource::Path(&config.secrets_key)).map_err(|e| Error::Secrets {
+            name: CACHE_SECRET.into(),
+            message: format!("no key to encrypt the cache with: {e}"),
+            store: config.secrets.clone(),
+            key: config.secrets_key.clone(),
+        })?;
+    vault.set(CACHE_SECRET, bytes);
+    atomic::replace(&config.cache, |temporary| {
+        vault.save_as(temporary).map_err(std::io::Error::other)
+    })
+}
+
+/// Drops the cached leaderboards that `keep` says no to, and entries whose
+/// key is not a board at all, returning their keys in order.
+///
+/// # Errors
+///
+/// When the cache cannot be read or written.
+pub fn prune(
+    config: &Config,
+    keep: impl Fn(Board) -> bool,
+    warnings: &mut Warnings,
+) -> Result<Vec<String>> {
+    let _lock = atomic::lock(&config.cache)?;
+    let mut cache = read(config, warnings)?;
+    let board = |key: &str| {
+        let (year, leaderboard) = key.split_once('/')?;
+        Some(Board {
+            year: year.parse().ok()?,
+            leaderboard: leaderboard.parse().ok()?,
+        })
+    };
+    let removed: Vec<String> = cache
+        .keys()
+        .filter(|key| !board(key).is_some_and(&keep))
+        .cloned()
+        .sorted()
+        .collect();
+    if !removed.is_empty() {
+        for key in &removed {
+            cache.remove(key);
+        }
+        write(config, &cache)?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample;
+
+    fn cache() -> Cache {
+        let entry = CacheEntry {
+            timestamp: Local::now(),
+            data: sample::leaderboard(2024, sample::SEED),
+            validators: Validators {
+                etag: Some("\"v1\"".into()),
+                last_modified: None,
+            },
+        };
+        HashMap::from([("2024/100001".to_string(), entry)])
+    }
+
+    #[test]
+    fn cache_round_trips() {
+        let file = CacheFile {
+            version: CACHE_VERSION,
+            boards: &cache(),
+        };
+        let parsed = parse(serde_json::to_value(&file).unwrap()).unwrap();
+        let entry = &parsed["2024/100001"];
+        assert_eq!(entry.data.owner_id, 100_001);
+        assert_eq!(entry.validators.etag.as_deref(), Some("\"v1\""));
+    }
+
+    #[test]
+    fn version_1_cache_is_migrated() {
+        let parsed = parse(serde_json::to_value(cache()).unwrap()).unwrap();
+        assert!(parsed.contains_key("2024/100001"));
+    }
+
+    #[test]
+    fn baseline_cache_keys_get_the_year() {
+        let baseline = r#"{"649161": {
+            "timestamp": "2023-12-01T06:12:00+01:00",
+            "data": {"event": "2023", "owner_id": 649161, "members": {}}
+        }}"#;
+        let parsed = parse(serde_json::from_str(baseline).unwrap()).unwrap();
+        assert_eq!(parsed.keys().collect::<Vec<_>>(), ["2023/649161"]);
+        assert_eq!(parsed["2023/649161"].data.owner_id, 649_161);
+    }
+
+    #[test]
+    fn compressed_cache_round_trips() {
+        let text = serde_json::to_vec(&cache()).unwrap();
+        let inflated = inflate(deflate(&text)).unwrap();
+        assert_eq!(inflated, text);
+        assert_eq!(inflate(text.clone()).unwrap(), text);
+    }
+
+    #[test]
+    fn unreadable_caches_are_refused() {
+        let parse = |text| parse(serde_json::from_str(text).unwrap());
+        assert!(parse("{\"2024/1\": {\"timestamp\": 0}}").is_err());
+        assert!(parse("{\"version\": 99, \"boards\": {}}").is_err());
+        assert!(parse("{\"version\": 2, \"boards\": []}").is_err());
+    }
+}
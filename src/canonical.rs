@@ -0,0 +1,84 @@
+//! One display name per member id across several boards.
+use crate::Aoc;
+use std::collections::HashMap;
+
+/// Picks a name for every member id seen on `boards`. A name set on the board
+/// the member owns wins, then any non-anonymous name; remaining ties go to
+/// the alphabetically first name so the choice is stable between runs.
+pub fn canonical_names(boards: &[Aoc]) -> HashMap<i32, String> {
+    let mut best: HashMap<i32, (bool, String)> = HashMap::new();
+    for board in boards {
+        for member in board.members.values() {
+            let Some(name) = &member.name else {
+                continue;
+            };
+            let candidate = (member.id != board.owner_id, name.clone());
+            best.entry(member.id)
+                .and_modify(|current| {
+                    if candidate < *current {
+                        current.clone_from(&candidate);
+                    }
+                })
+                .or_insert(candidate);
+        }
+    }
+    best.into_iter().map(|(id, (_, name))| (id, name)).collect()
+}
+
+pub fn apply(boards: &mut [Aoc]) {
+    let names = canonical_names(boards);
+    for board in boards {
+        for member in board.members.values_mut() {
+            if let Some(name) = names.get(&member.id) {
+                member.name = Some(name.clone());
+            }
+        }
+    }
+}
+
+/// Names the members of `boards` whose id is in `nicknames`.
+pub fn nickname(boards: &mut [Aoc], nicknames: &HashMap<String, String>) {
+    for board in boards {
+        for member in board.members.values_mut() {
+            if let Some(name) = nicknames.get(&member.id.to_string()) {
+                member.name = Some(name.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::member;
+    use crate::Member;
+
+    fn board(owner_id: i32, members: &[(i32, Option<&str>)]) -> Aoc {
+        Aoc {
+            owner_id,
+            ..crate::fixtures::board(members.iter().map(|&(id, name)| Member {
+                name: name.map(str::to_string),
+                ..member(id, "", &[])
+            }))
+        }
+    }
+
+    #[test]
+    fn same_id_gets_one_name_on_every_board() {
+        let mut boards = vec![
+            board(1, &[(1, Some("owner")), (7, Some("zed")), (9, None)]),
+            board(
+                2,
+                &[(2, Some("other")), (7, Some("Zed Z")), (9, Some("nine"))],
+            ),
+            board(7, &[(7, Some("Zed Zedsson"))]),
+        ];
+        apply(&mut boards);
+        for board in &boards {
+            assert_eq!(board.members["7"].name.as_deref(), Some("Zed Zedsson"));
+            if let Some(nine) = board.members.get("9") {
+                assert_eq!(nine.name.as_deref(), Some("nine"));
+            }
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Cumulative score per member over December, drawn with plotters.
+use crate::error::{Error, Result};
+use crate::scoring::Counting;
+use crate::{unlock, Aoc};
+use plotters::coord::Shift;
+use plotters::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+const WIDTH: u32 = 1024;
+/// Height of each board's chart; boards are stacked.
+const HEIGHT: u32 = 600;
+
+/// A member's name and running score, against days since the first unlock
+/// with day 1 at 1.0.
+type Series = (String, Vec<(f64, usize)>);
+
+#[allow(clippy::cast_precision_loss)]
+fn series(aoc: &Aoc, counting: Counting) -> Vec<Series> {
+    let start = unlock(aoc.year(), 1).timestamp();
+    let mut series: Vec<Series> = Vec::new();
+    let mut index: HashMap<i32, usize> = HashMap::new();
+    for (event, points) in counting.scored(aoc) {
+        let day = 1.0 + (event.timestamp.timestamp() - start) as f64 / 86_400.0;
+        let i = *index.entry(event.member_id).or_insert_with(|| {
+            series.push((event.member.to_string(), vec![(day, 0)]));
+            series.len() - 1
+        });
+        let total = series[i].1.last().map_or(0, |(_, total)| *total) + points;
+        series[i].1.push((day, total));
+    }
+    series.sort_by_key(|(_, points)| std::cmp::Reverse(points.last().map(|(_, t)| *t)));
+    series
+}
+
+fn draw<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    aoc: &Aoc,
+    counting: Counting,
+) -> std::result::Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let series = series(aoc, counting);
+    let max = series
+        .iter()
+        .filter_map(|(_, points)| points.last().map(|(_, t)| *t))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    area.fill(&WHITE)?;
+    let mut chart = ChartBuilder::on(area)
+        .caption(
+            format!("{} leaderboard {}", aoc.year(), aoc.owner_id),
+            ("sans-serif", 24),
+        )
+        .margin(12)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d(1f64..26f64, 0..max + max / 10)?;
+    chart
+        .configure_mesh()
+        .x_desc("December")
+        .y_desc("Points")
+        .x_labels(25)
+        .x_label_formatter(&|day| format!("{day:.0}"))
+        .draw()?;
+    for (i, (member, points)) in series.iter().enumerate() {
+        chart
+            .draw_series(LineSeries::new(
+                points.iter().copied(),
+                Palette99::pick(i).stroke_width(2),
+            ))?
+            .label(member)
+            .legend(move |(x, y)| {
+                PathElement::new(
+                    vec![(x, y), (x + 16, y)],
+                    Palette99::pick(i).stroke_width(2),
+                )
+            });
+    }
+    chart
+        .configure_series_labels()
+        .position(SeriesLabelPosition::UpperLeft)
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    Ok(())
+}
+
+fn draw_all<DB: DrawingBackend>(
+    root: &DrawingArea<DB, Shift>,
+    boards: &[Aoc],
+    counting: Counting,
+) -> std::result::Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    for (area, aoc) in root.split_evenly((boards.len(), 1)).iter().zip(boards) {
+        draw(area, aoc, counting)?;
+    }
+    root.present()
+}
+
+/// Writes one chart per board, stacked, as SVG when `path` ends in `.svg`
+/// and PNG otherwise, scored by `counting`.
+///
+/// # Errors
+///
+/// When the chart cannot be drawn or written.
+pub fn write_chart(path: &Path, boards: &[Aoc], counting: Counting) -> Result<()> {
+    let error = |e: &dyn std::fmt::Display| Error::Chart {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    };
+    if boards.is_empty() {
+        return Err(error(&"no leaderboards to chart"));
+    }
+    let height = HEIGHT * u32::try_from(boards.len()).unwrap_or(1);
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"))
+    {
+        let root = SVGBackend::new(path, (WIDTH, height)).into_drawing_area();
+        draw_all(&root, boards, counting).map_err(|e| error(&e))
+    } else {
+        let root = BitMapBackend::new(path, (WIDTH, height)).into_drawing_area();
+        draw_all(&root, boards, counting).map_err(|e| error(&e))
+    }
+}
@@ -0,0 +1,82 @@
+//! ANSI colors for the text format, picked with `--color`.
+use crate::stable_hash;
+use anstyle::{AnsiColor, Style};
+use clap::ValueEnum;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+/// When to color the text format.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ColorChoice {
+    /// When printing to a terminal and `NO_COLOR` is not set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// Member colors, told apart on dark and light terminals alike.
+const MEMBERS: [AnsiColor; 10] = [
+    AnsiColor::Cyan,
+    AnsiColor::Magenta,
+    AnsiColor::Green,
+    AnsiColor::Blue,
+    AnsiColor::Red,
+    AnsiColor::BrightCyan,
+    AnsiColor::BrightMagenta,
+    AnsiColor::BrightGreen,
+    AnsiColor::BrightBlue,
+    AnsiColor::BrightRed,
+];
+
+static ENABLED: OnceLock<bool> = OnceLock::new();
+
+/// Colors the rest of the run's text output as `choice` says.
+pub fn set(choice: ColorChoice) {
+    ENABLED.get_or_init(|| match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            std::env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+                && std::io::stdout().is_terminal()
+        }
+    });
+}
+
+/// `text` in `style`, or as it is when colors are off.
+fn paint(text: impl Display, style: Style) -> String {
+    if ENABLED.get().copied().unwrap_or(false) {
+        format!("{}{text}{}", style.render(), style.render_reset())
+    } else {
+        text.to_string()
+    }
+}
+
+/// A day heading or section title.
+pub fn heading(text: impl Display) -> String {
+    paint(text, Style::new().bold())
+}
+
+/// `text` in the color of `member`, the same one every run. Pad `text`
+/// before coloring it, as the escape codes count towards the width.
+pub fn member(text: impl Display, member: &str) -> String {
+    let hash = stable_hash(member.bytes());
+    let color = MEMBERS[usize::try_from(hash % MEMBERS.len() as u64).unwrap()];
+    paint(text, color.on_default())
+}
+
+/// A star like `07-2`, silver for part 1 and gold for part 2.
+pub fn star(text: impl Display, part: u32) -> String {
+    let color = if part == 2 {
+        AnsiColor::Yellow
+    } else {
+        AnsiColor::White
+    };
+    paint(text, color.on_default().bold())
+}
+
+/// A score worth pointing out, like one of the first three places.
+pub fn highlight(text: impl Display) -> String {
+    paint(text, AnsiColor::BrightYellow.on_default().bold())
+}
@@ -0,0 +1,137 @@
+//! Every board's events in one timeline, with one leaderboard that counts
+//! each person once.
+use crate::color;
+use crate::layout;
+use crate::locale;
+use crate::merge::{self, Person};
+use crate::render::{cell, LeaderboardReport, ScoredEvent};
+use crate::Report;
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt::Write as _;
+
+/// A star and every board it was earned on.
+#[derive(Debug, Serialize)]
+pub struct CombinedEvent {
+    #[serde(flatten)]
+    pub event: Report,
+    /// Like `2024/12345`.
+    pub boards: Vec<String>,
+    /// The points on all those boards together.
+    pub points: usize,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub new: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Combined {
+    pub events: Vec<CombinedEvent>,
+    pub standings: Vec<Person>,
+}
+
+/// Interleaves the events of `reports`, a star earned by the same member on
+/// several boards becoming one event, next to the `standings` from
+/// [`merge::merge`].
+pub fn combine(reports: &[LeaderboardReport], standings: Vec<Person>) -> Combined {
+    let events = reports
+        .iter()
+        .flat_map(|report| {
+            let board = format!("{}/{}", report.year, report.leaderboard);
+            report
+                .events
+                .iter()
+                .map(move |event| (board.clone(), event))
+        })
+        .into_group_map_by(|(_, scored)| {
+            (
+                scored.event.timestamp,
+                scored.event.member_id,
+                scored.event.star,
+            )
+        })
+        .into_values()
+        .map(|events| {
+            let (_, first) = events[0];
+            CombinedEvent {
+                event: first.event.clone(),
+                points: events.iter().map(|(_, scored)| scored.points).sum(),
+                new: events.iter().any(|(_, ScoredEvent { new, .. })| *new),
+                boards: events.into_iter().map(|(board, _)| board).collect(),
+            }
+        })
+        .sorted_by(|a, b| {
+            a.event
+                .timestamp
+                .cmp(&b.event.timestamp)
+                .then_with(|| a.event.member.cmp(&b.event.member))
+        })
+        .collect();
+    Combined { events, standings }
+}
+
+pub fn print(combined: &Combined) {
+    let mut day = None;
+    let separator = if layout::stacked() { "\n    " } else { "\t" };
+    for CombinedEvent {
+        event,
+        boards,
+        points,
+        new,
+    } in &combined.events
+    {
+        if day != Some(event.timestamp.date_naive()) {
+            println!(
+                "\n{}",
+                color::heading(locale::format(&event.timestamp, "%B %e, %Y"))
+            );
+            day = Some(event.timestamp.date_naive());
+        }
+        println!(
+            "{} {} {}{separator}{} [{}] ({})  {}",
+            if *new { '*' } else { ' ' },
+            event.timestamp.time(),
+            color::member(layout::name(&event.member), &event.member),
+            color::star(event.star, event.part()),
+            points,
+            event.elapsed_string(),
+            boards.join(", ")
+        );
+    }
+    merge::print(&combined.standings);
+}
+
+pub fn markdown(combined: &Combined) -> String {
+    let mut md = String::from("## All leaderboards\n");
+    let mut day = None;
+    for CombinedEvent {
+        event,
+        boards,
+        points,
+        ..
+    } in &combined.events
+    {
+        if day != Some(event.timestamp.date_naive()) {
+            write!(
+                md,
+                "\n### {}\n\n| Time | Member | Star | Points | Elapsed | Boards |\n\
+                 |---|---|---|--:|--:|---|\n",
+                locale::format(&event.timestamp, "%B %-d, %Y")
+            )
+            .unwrap();
+            day = Some(event.timestamp.date_naive());
+        }
+        writeln!(
+            md,
+            "| {} | {} | {} | {points} | {} | {} |",
+            event.timestamp.time(),
+            cell(&event.member),
+            event.star,
+            event.elapsed_string(),
+            boards.join(", ")
+        )
+        .unwrap();
+    }
+    md.push('\n');
+    md.push_str(&merge::markdown(&combined.standings).replacen("## ", "### ", 1));
+    md
+}
@@ -0,0 +1,170 @@
+//! Two members side by side: who got each star first and how the scores
+//! drifted apart.
+use crate::error::Result;
+use crate::filter::{self, Ambiguous, MemberSpec};
+use crate::locale;
+use crate::scoring::Counting;
+use crate::warnings::Warnings;
+use crate::{display_names, duration_string, Aoc, Member};
+use chrono::{DateTime, Duration, Local, TimeZone};
+use serde::Serialize;
+
+/// One star that at least one of the members has.
+#[derive(Debug, Serialize)]
+pub struct Duel {
+    pub day: u32,
+    pub part: u32,
+    pub a: Option<DateTime<Local>>,
+    pub b: Option<DateTime<Local>>,
+    /// The member who got the star first, or the only one who has it.
+    pub winner: Option<String>,
+    /// How much earlier the winner was, zero when only one has the star.
+    #[serde(rename = "margin_seconds", serialize_with = "crate::seconds")]
+    pub margin: Duration,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HeadToHead {
+    pub year: i32,
+    pub leaderboard: i32,
+    pub a: String,
+    pub b: String,
+    pub duels: Vec<Duel>,
+    pub wins_a: usize,
+    pub wins_b: usize,
+    /// A's points minus B's, counting the stars of every day up to this one.
+    pub difference: Vec<(u32, i64)>,
+}
+
+fn star_time(member: &Member, day: u32, part: u32) -> Option<DateTime<Local>> {
+    let star = member.completion_day_level.get(&day)?.get(&part)?;
+    Local.timestamp_opt(star.get_star_ts, 0).single()
+}
+
+/// The member `spec` picks on `aoc`, or `None` when it picks nobody there.
+///
+/// # Errors
+///
+/// When `spec` picks several members, as a duel needs exactly one a side.
+pub fn find<'a>(aoc: &'a Aoc, spec: &MemberSpec) -> Result<Option<&'a Member>> {
+    let ids = filter::resolve(
+        std::slice::from_ref(spec),
+        &aoc.members,
+        Ambiguous::Error,
+        &mut Warnings::default(),
+    )?;
+    Ok(aoc.members.values().find(|m| ids.contains(&m.id)))
+}
+
+/// Compares `a` and `b` on `aoc`, star by star, with points from `counting`.
+pub fn compare(aoc: &Aoc, a: &Member, b: &Member, counting: Counting) -> HeadToHead {
+    let names = display_names(&aoc.members);
+    let (name_a, name_b) = (names[&a.id].clone(), names[&b.id].clone());
+    let mut duels = Vec::new();
+    for day in 1..=25 {
+        for part in 1..=2 {
+            let (time_a, time_b) = (star_time(a, day, part), star_time(b, day, part));
+            let (winner, margin) = match (time_a, time_b) {
+                (None, None) => continue,
+                (Some(_), None) => (Some(name_a.clone()), Duration::zero()),
+                (None, Some(_)) => (Some(name_b.clone()), Duration::zero()),
+                (Some(x), Some(y)) if x < y => (Some(name_a.clone()), y - x),
+                (Some(x), Some(y)) if y < x => (Some(name_b.clone()), x - y),
+                (Some(_), Some(_)) => (None, Duration::zero()),
+            };
+            duels.push(Duel {
+                day,
+                part,
+                a: time_a,
+                b: time_b,
+                winner,
+                margin,
+            });
+        }
+    }
+    let mut per_day = [0i64; 25];
+    for (event, points) in counting.scored(aoc) {
+        let points = i64::try_from(points).unwrap();
+        if event.member_id == a.id {
+            per_day[event.day() as usize - 1] += points;
+        } else if event.member_id == b.id {
+            per_day[event.day() as usize - 1] -= points;
+        }
+    }
+    let last = duels.last().map_or(0, |duel| duel.day);
+    let difference = (1..=last)
+        .scan(0, |total, day| {
+            *total += per_day[day as usize - 1];
+            Some((day, *total))
+        })
+        .collect();
+    let wins = |name: &str| {
+        duels
+            .iter()
+            .filter(|duel| duel.winner.as_deref() == Some(name))
+            .count()
+    };
+    HeadToHead {
+        year: aoc.year(),
+        leaderboard: aoc.owner_id,
+        wins_a: wins(&name_a),
+        wins_b: wins(&name_b),
+        a: name_a,
+        b: name_b,
+        duels,
+        difference,
+    }
+}
+
+pub fn print(h2h: &HeadToHead) {
+    println!(
+        "\n{} vs {} on leaderboard {} ({})",
+        h2h.a, h2h.b, h2h.leaderboard, h2h.year
+    );
+    for (i, duel) in h2h.duels.iter().enumerate() {
+        let time = |t: Option<DateTime<Local>>| {
+            t.map_or_else(|| "-".to_string(), |t| locale::format(&t, "%b %e %H:%M:%S"))
+        };
+        let result = match (&duel.winner, duel.a.zip(duel.b)) {
+            (Some(winner), Some(_)) => format!("{winner} by {}", duration_string(duel.margin)),
+            (Some(winner), None) => format!("{winner} only"),
+            (None, _) => "tie".to_string(),
+        };
+        print!(
+            "  {:02}-{}  {:16}  {:16}  {result}",
+            duel.day,
+            duel.part,
+            time(duel.a),
+            time(duel.b)
+        );
+        // The running score difference after the day's last star.
+        if h2h.duels.get(i + 1).is_none_or(|next| next.day != duel.day) {
+            print!("  ({:+})", h2h.difference[duel.day as usize - 1].1);
+        }
+        println!();
+    }
+    let total = h2h.difference.last().map_or(0, |(_, total)| *total);
+    println!(
+        "\n  Stars first: {} {}, {} {}",
+        h2h.a, h2h.wins_a, h2h.b, h2h.wins_b
+    );
+    println!("  Score difference: {total:+} for {}", h2h.a);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{board, member};
+
+    #[test]
+    fn a_pattern_picking_two_members_is_refused() {
+        let aoc = board([
+            member(1, "Alice", &[]),
+            member(2, "alfred", &[]),
+            member(3, "Bob", &[]),
+        ]);
+        assert!(find(&aoc, &"al".parse().unwrap()).is_err());
+        let bob = find(&aoc, &"bo".parse().unwrap()).unwrap();
+        assert_eq!(bob.map(|m| m.id), Some(3));
+    }
+}
@@ -0,0 +1,299 @@
+//! Settings read from `aoc-timeline.toml`, falling back to the built-in defaults.
+//!
+//! Files live in the platform's config and cache directories, like
+//! `$XDG_CONFIG_HOME/aoc-timeline` and `$XDG_CACHE_HOME/aoc-timeline` on
+//! Linux, unless they are in the current directory from before.
+use crate::error::{Error, Result};
+use crate::scoring::TieBreak;
+use crate::throttle;
+use crate::{CACHEFILE, LEADERBOARDS, YEAR};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+pub const CONFIGFILE: &str = "aoc-timeline.toml";
+/// The directory under the platform's config and cache directories.
+const DIR: &str = "aoc-timeline";
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub year: i32,
+    pub leaderboards: Vec<i32>,
+    pub cache: PathBuf,
+    pub secrets: PathBuf,
+    pub secrets_key: PathBuf,
+    pub notify: Notify,
+    pub digest: Option<Digest>,
+    pub history: Option<History>,
+    /// Database file that records every fetch and star, with the `sqlite`
+    /// feature.
+    pub database: Option<PathBuf>,
+    /// Days that score no points, added to `--exclude-day`.
+    pub exclude_days: Vec<u32>,
+    /// How to order members on the same score, when `--tie-break` is not
+    /// given, like `["stars", "last-star"]`.
+    pub tie_break: Vec<TieBreak>,
+    /// Members to count as one person with `--merge`, by name,
+    /// `Anonymous#ID` or id, like `"alice-work" = "Alice"`.
+    pub aliases: HashMap<String, String>,
+    /// Members to keep or drop per leaderboard id.
+    pub members: HashMap<String, MemberLists>,
+    /// The IANA time zone to show times in, like `"UTC"`, when
+    /// `--timezone` is not given.
+    pub timezone: Option<String>,
+    /// The language of month names and dates, like `"de"`, when `--locale`
+    /// is not given.
+    pub locale: Option<String>,
+    /// Names to show for member ids, like `"123456" = "Dana"` for an
+    /// anonymous member.
+    pub nicknames: HashMap<String, String>,
+    pub retry: Retry,
+    pub http: Http,
+    /// Minutes before a cached leaderboard is fetched again, at least 15.
+    pub cache_ttl: Option<i64>,
+    /// `cache_ttl` for single leaderboards, by id.
+    pub leaderboard_cache_ttl: HashMap<String, i64>,
+    /// Encrypt the cache with the secrets key, as members' names and times
+    /// are in it.
+    pub encrypt_cache: bool,
+    /// Compress the cache with gzip.
+    pub compress_cache: bool,
+}
+
+/// Who counts on one leaderboard, under `[members.<leaderboard>]`, by name,
+/// `name#id` or `Anonymous#ID`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MemberLists {
+    /// Only these members, or everyone when empty.
+    pub include: Vec<String>,
+    /// Never these members, like inactive accounts and bots.
+    pub exclude: Vec<String>,
+}
+
+/// Where to announce new stars, under `[notify]`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Notify {
+    pub discord_webhook: Option<String>,
+    pub slack: Vec<SlackWebhook>,
+    /// Send to Telegram with the `telegram_token` and `telegram_chat`
+    /// secrets.
+    pub telegram: bool,
+    /// URLs that get a JSON POST for every new star.
+    pub webhooks: Vec<String>,
+    /// Shell command run for every new star.
+    pub exec: Option<String>,
+    /// Pop up a desktop notification for every new star while watching;
+    /// other runs ignore it.
+    pub desktop: bool,
+}
+
+/// A Slack incoming webhook, under `[[notify.slack]]`.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SlackWebhook {
+    pub webhook: String,
+    /// Only these leaderboards, or every one when empty.
+    #[serde(default)]
+    pub leaderboards: Vec<i32>,
+    /// Post new stars as they are found, not only reports.
+    #[serde(default = "yes")]
+    pub new_stars: bool,
+}
+
+/// Where the `digest` subcommand sends its email, under `[digest]`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Digest {
+    pub smtp_server: String,
+    /// Defaults to 587 with STARTTLS and 465 without.
+    pub port: Option<u16>,
+    /// Upgrade a plain connection rather than connecting with TLS.
+    #[serde(default = "yes")]
+    pub starttls: bool,
+    /// Log in as this user with the `smtp_password` secret.
+    pub username: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// Where fetched leaderboards are archived, under `[history]`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct History {
+    pub dir: PathBuf,
+    /// Keep only this many snapshots per leaderboard.
+    pub keep: Option<usize>,
+    /// Drop snapshots older than this many days.
+    pub max_days: Option<i64>,
+}
+
+/// How often to try a request to adventofcode.com that fails with a server
+/// error or a dropped connection, under `[retry]`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Retry {
+    /// Tries per request, `1` to never retry.
+    pub attempts: u32,
+    /// Milliseconds to wait before the first retry, doubled before each one
+    /// after that.
+    pub delay_ms: u64,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Retry {
+            attempts: 3,
+            delay_ms: 1000,
+        }
+    }
+}
+
+/// How requests go out, to adventofcode.com and to webhooks, under `[http]`.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Http {
+    /// Seconds a whole request may take.
+    pub timeout_secs: u64,
+    /// Seconds to wait for a connection.
+    pub connect_timeout_secs: u64,
+    /// A proxy for every request, like `"http://proxy.example.com:3128"`.
+    /// Otherwise `HTTPS_PROXY`, `ALL_PROXY` and `NO_PROXY` are honoured.
+    pub proxy: Option<String>,
+    /// How adventofcode.com can reach whoever runs this, like an email
+    /// address, added to the User-Agent.
+    pub contact: Option<String>,
+}
+
+impl Default for Http {
+    fn default() -> Self {
+        Http {
+            timeout_secs: 30,
+            connect_timeout_secs: 10,
+            proxy: None,
+            contact: None,
+        }
+    }
+}
+
+impl Config {
+    /// Minutes before the cached `leaderboard` is fetched again.
+    pub fn cache_ttl(&self, leaderboard: i32) -> i64 {
+        self.leaderboard_cache_ttl
+            .get(&leaderboard.to_string())
+            .or(self.cache_ttl.as_ref())
+            .copied()
+            .unwrap_or(throttle::MINUTES)
+    }
+}
+
+fn yes() -> bool {
+    true
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            year: YEAR,
+            leaderboards: LEADERBOARDS.to_vec(),
+            cache: located(CACHEFILE, dirs::cache_dir(), "leaderboards.json"),
+            secrets: located("secrets.json", dirs::config_dir(), "secrets.json"),
+            secrets_key: located(".secrets.key", dirs::config_dir(), "secrets.key"),
+            notify: Notify::default(),
+            digest: None,
+            history: None,
+            database: None,
+            exclude_days: Vec::new(),
+            tie_break: Vec::new(),
+            aliases: HashMap::new(),
+            members: HashMap::new(),
+            nicknames: HashMap::new(),
+            timezone: None,
+            locale: None,
+            retry: Retry::default(),
+            http: Http::default(),
+            cache_ttl: None,
+            leaderboard_cache_ttl: HashMap::new(),
+            encrypt_cache: false,
+            compress_cache: true,
+        }
+    }
+}
+
+/// Where a file goes when the config does not say: `legacy` in the current
+/// directory if it is there, otherwise `name` in the tool's directory under
+/// `base`, or `legacy` again on a platform without one.
+fn located(legacy: &str, base: Option<PathBuf>, name: &str) -> PathBuf {
+    let legacy = std::path::absolute(legacy).unwrap_or_else(|_| PathBuf::from(legacy));
+    match base {
+        Some(base) if !legacy.exists() => base.join(DIR).join(name),
+        _ => legacy,
+    }
+}
+
+/// Loads `path`, or when no path is given `aoc-timeline.toml` in the current
+/// directory or `config.toml` in the config directory, whichever exists.
+/// Relative paths in the file are relative to the file.
+///
+/// # Errors
+///
+/// When the file cannot be read or is not valid configuration.
+pub fn load(path: Option<&Path>) -> Result<Config> {
+    let found = dirs::config_dir().map(|dir| dir.join(DIR).join("config.toml"));
+    let path = match (path, &found) {
+        (Some(path), _) => path,
+        _ if Path::new(CONFIGFILE).exists() => Path::new(CONFIGFILE),
+        (None, Some(found)) if found.exists() => found,
+        _ => return Ok(Config::default()),
+    };
+    debug!("config from {}", path.display());
+    let text = read_to_string(path).map_err(|source| Error::Config {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let mut config: Config = toml::from_str(&text).map_err(|source| Error::ConfigSyntax {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    if let Some(dir) = path.parent() {
+        config.cache = dir.join(&config.cache);
+        config.secrets = dir.join(&config.secrets);
+        config.secrets_key = dir.join(&config.secrets_key);
+        if let Some(history) = &mut config.history {
+            history.dir = dir.join(&history.dir);
+        }
+        if let Some(database) = &mut config.database {
+            *database = dir.join(&*database);
+        }
+    }
+    if cfg!(not(feature = "sqlite")) && config.database.is_some() {
+        return Err(Error::Usage {
+            message: "database needs a build with '--features sqlite'".into(),
+        });
+    }
+    let too_short = config
+        .cache_ttl
+        .iter()
+        .chain(config.leaderboard_cache_ttl.values())
+        .any(|&ttl| ttl < throttle::MINUTES);
+    if too_short {
+        return Err(Error::Usage {
+            message: format!(
+                "a cache_ttl must be at least {} minutes, as adventofcode.com asks",
+                throttle::MINUTES
+            ),
+        });
+    }
+    if let Some(proxy) = &config.http.proxy {
+        if reqwest::Proxy::all(proxy).is_err() {
+            return Err(Error::Usage {
+                message: format!("proxy '{proxy}' is not a valid URL"),
+            });
+        }
+    }
+    Ok(config)
+}
@@ -0,0 +1,92 @@
+//! How the group did on each day, to find the hardest puzzles.
+use crate::stats::{median, time};
+use crate::{automatic, unlock, Aoc};
+use chrono::Duration;
+use serde::Serialize;
+
+/// Fastest, median and slowest of a set of times.
+#[derive(Debug, Serialize)]
+pub struct Spread {
+    pub count: usize,
+    #[serde(rename = "fastest_seconds", serialize_with = "crate::optional_seconds")]
+    pub fastest: Option<Duration>,
+    #[serde(rename = "median_seconds", serialize_with = "crate::optional_seconds")]
+    pub median: Option<Duration>,
+    #[serde(rename = "slowest_seconds", serialize_with = "crate::optional_seconds")]
+    pub slowest: Option<Duration>,
+}
+
+impl Spread {
+    fn of(mut times: Vec<Duration>) -> Spread {
+        times.sort();
+        Spread {
+            count: times.len(),
+            fastest: times.first().copied(),
+            median: median(&times),
+            slowest: times.last().copied(),
+        }
+    }
+}
+
+/// One day's solves: parts timed from the unlock, the gap from each
+/// member's part 1 to their part 2.
+#[derive(Debug, Serialize)]
+pub struct DayStats {
+    pub day: u32,
+    pub part1: Spread,
+    pub part2: Spread,
+    pub gap: Spread,
+}
+
+/// Stats for every day anyone has a star on.
+pub fn days(aoc: &Aoc) -> Vec<DayStats> {
+    let year = aoc.year();
+    (1..=25)
+        .filter_map(|day| {
+            let mut part1 = Vec::new();
+            let mut part2 = Vec::new();
+            let mut gap = Vec::new();
+            for member in aoc.members.values() {
+                let Some(stars) = member.completion_day_level.get(&day) else {
+                    continue;
+                };
+                let elapsed = |part| {
+                    let star = stars.get(&part).filter(|_| !automatic(day, part))?;
+                    Some(Duration::seconds(
+                        star.get_star_ts - unlock(year, day).timestamp(),
+                    ))
+                };
+                part1.extend(elapsed(1));
+                part2.extend(elapsed(2));
+                if let (Some(first), Some(second)) = (elapsed(1), elapsed(2)) {
+                    gap.push(second - first);
+                }
+            }
+            (!part1.is_empty()).then(|| DayStats {
+                day,
+                part1: Spread::of(part1),
+                part2: Spread::of(part2),
+                gap: Spread::of(gap),
+            })
+        })
+        .collect()
+}
+
+pub fn print(days: &[DayStats]) {
+    println!("\nPer day (solved: fastest / median / slowest):");
+    for day in days {
+        let spread = |spread: &Spread| {
+            format!(
+                "{:2}: {} / {} / {}",
+                spread.count,
+                time(spread.fastest),
+                time(spread.median),
+                time(spread.slowest)
+            )
+        };
+        println!("  Day {:2}", day.day);
+        println!("    part 1  {}", spread(&day.part1));
+        println!("    part 2  {}", spread(&day.part2));
+        println!("    gap     {}", spread(&day.gap));
+    }
+}
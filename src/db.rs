@@ -0,0 +1,296 @@
+//! Every fetch and every star kept in a database, so history can be queried
+//! without replaying JSON snapshots.
+use crate::config::Config;
+use crate::error::Result;
+use crate::export::csv_field;
+use crate::layout;
+use crate::render::{cell, Format};
+use crate::{duration_string, html, timeline, unlock, Aoc, Board};
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use clap::ValueEnum;
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::Path;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS fetches (
+    year INTEGER NOT NULL,
+    leaderboard INTEGER NOT NULL,
+    fetched_at INTEGER NOT NULL,
+    members INTEGER NOT NULL,
+    stars INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS stars (
+    year INTEGER NOT NULL,
+    leaderboard INTEGER NOT NULL,
+    member_id INTEGER NOT NULL,
+    member TEXT NOT NULL,
+    day INTEGER NOT NULL,
+    part INTEGER NOT NULL,
+    timestamp INTEGER NOT NULL,
+    elapsed_seconds INTEGER NOT NULL,
+    PRIMARY KEY (year, leaderboard, member_id, day, part)
+);
+CREATE INDEX IF NOT EXISTS stars_member ON stars (member);
+CREATE INDEX IF NOT EXISTS stars_day ON stars (year, day, part);
+";
+
+/// Opens the database at `path`, creating the tables on first use.
+///
+/// # Errors
+///
+/// When the file cannot be opened or is not a database.
+pub fn open(path: &Path) -> Result<Connection> {
+    let connection = Connection::open(path)?;
+    connection.execute_batch(SCHEMA)?;
+    Ok(connection)
+}
+
+/// Records a fetch of `board` and any stars not stored yet, renaming members
+/// whose name changed.
+///
+/// # Errors
+///
+/// When the database cannot be written.
+pub fn store(connection: &mut Connection, board: Board, aoc: &Aoc) -> Result<()> {
+    let transaction = connection.transaction()?;
+    transaction.execute(
+        "INSERT INTO fetches VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            board.year,
+            board.leaderboard,
+            Utc::now().timestamp(),
+            i64::try_from(aoc.members.len()).unwrap(),
+            aoc.members.values().map(|m| m.stars).sum::<i32>(),
+        ],
+    )?;
+    {
+        let mut insert = transaction.prepare(
+            "INSERT INTO stars VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+             ON CONFLICT DO UPDATE SET member = excluded.member",
+        )?;
+        for event in timeline(aoc) {
+            let elapsed = event.timestamp.timestamp() - unlock(board.year, event.day()).timestamp();
+            insert.execute(params![
+                board.year,
+                board.leaderboard,
+                event.member_id,
+                event.member,
+                event.day(),
+                event.part(),
+                event.timestamp.timestamp(),
+                elapsed,
+            ])?;
+        }
+    }
+    transaction.commit()?;
+    Ok(())
+}
+
+/// Stores a freshly fetched `aoc` when the config names a database.
+///
+/// # Errors
+///
+/// When the database cannot be opened or written.
+pub fn record(config: &Config, board: Board, aoc: &Aoc) -> Result<()> {
+    let Some(path) = &config.database else {
+        return Ok(());
+    };
+    store(&mut open(path)?, board, aoc)
+}
+
+/// How `query` orders the stars it finds.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum Order {
+    /// When they were earned
+    #[default]
+    Time,
+    /// Fastest solve first
+    Elapsed,
+}
+
+/// Which stored stars to return, every one when all filters are empty.
+#[derive(Debug, Default)]
+pub struct Query {
+    pub years: Vec<i32>,
+    pub leaderboards: Vec<i32>,
+    pub members: Vec<String>,
+    pub days: Vec<u32>,
+    pub part: Option<u32>,
+    pub since: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub order: Order,
+    pub limit: Option<usize>,
+}
+
+/// One row of the `stars` table.
+#[derive(Clone, Debug, Serialize)]
+pub struct StoredStar {
+    pub year: i32,
+    pub leaderboard: i32,
+    pub member_id: i32,
+    pub member: String,
+    pub day: u32,
+    pub part: u32,
+    pub timestamp: DateTime<Local>,
+    #[serde(rename = "elapsed_seconds", serialize_with = "crate::seconds")]
+    pub elapsed: Duration,
+}
+
+/// Adds `column IN (...)` for a non-empty filter.
+fn any_of<T: Into<Value> + Clone>(
+    column: &str,
+    values: &[T],
+    clauses: &mut Vec<String>,
+    params: &mut Vec<Value>,
+) {
+    if values.is_empty() {
+        return;
+    }
+    clauses.push(format!(
+        "{column} IN ({})",
+        vec!["?"; values.len()].join(", ")
+    ));
+    params.extend(values.iter().cloned().map(Into::into));
+}
+
+/// Stored stars matching every filter in `query`.
+///
+/// # Errors
+///
+/// When the database cannot be read.
+pub fn query(connection: &Connection, query: &Query) -> Result<Vec<StoredStar>> {
+    let mut clauses = vec!["1".to_string()];
+    let mut params = Vec::new();
+    any_of("year", &query.years, &mut clauses, &mut params);
+    any_of(
+        "leaderboard",
+        &query.leaderboards,
+        &mut clauses,
+        &mut params,
+    );
+    any_of("member", &query.members, &mut clauses, &mut params);
+    any_of("day", &query.days, &mut clauses, &mut params);
+    if let Some(part) = query.part {
+        clauses.push("part = ?".into());
+        params.push(part.into());
+    }
+    if let Some(since) = query.since {
+        clauses.push("timestamp >= ?".into());
+        params.push(since.timestamp().into());
+    }
+    if let Some(until) = query.until {
+        clauses.push("timestamp < ?".into());
+        params.push(until.timestamp().into());
+    }
+    let order = match query.order {
+        Order::Time => "timestamp",
+        Order::Elapsed => "elapsed_seconds, timestamp",
+    };
+    let limit = query
+        .limit
+        .map_or(-1, |limit| i64::try_from(limit).unwrap());
+    let sql = format!(
+        "SELECT year, leaderboard, member_id, member, day, part, timestamp, elapsed_seconds
+         FROM stars WHERE {} ORDER BY {order} LIMIT {limit}",
+        clauses.join(" AND ")
+    );
+    let mut statement = connection.prepare(&sql)?;
+    let rows = statement.query_map(params_from_iter(params), |row| {
+        Ok(StoredStar {
+            year: row.get(0)?,
+            leaderboard: row.get(1)?,
+            member_id: row.get(2)?,
+            member: row.get(3)?,
+            day: row.get(4)?,
+            part: row.get(5)?,
+            timestamp: Local.timestamp_opt(row.get(6)?, 0).unwrap(),
+            elapsed: Duration::seconds(row.get(7)?),
+        })
+    })?;
+    Ok(rows.collect::<rusqlite::Result<_>>()?)
+}
+
+/// Prints `stars` as a table, JSON, CSV, Markdown or an HTML page.
+pub fn print(stars: &[StoredStar], format: Format) {
+    match format {
+        Format::Text => {
+            for star in stars {
+                println!(
+                    "{} {:4} {:10} {}\t{:02}-{} ({})",
+                    star.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    star.year,
+                    star.leaderboard,
+                    layout::name(&star.member),
+                    star.day,
+                    star.part,
+                    duration_string(star.elapsed)
+                );
+            }
+        }
+        Format::Json => println!("{}", serde_json::to_string_pretty(stars).unwrap()),
+        Format::Ndjson => {
+            for star in stars {
+                println!("{}", serde_json::to_string(star).unwrap());
+            }
+        }
+        Format::Csv => {
+            println!("year,leaderboard,timestamp,member_id,member,day,part,elapsed_seconds");
+            for star in stars {
+                println!(
+                    "{},{},{},{},{},{},{},{}",
+                    star.year,
+                    star.leaderboard,
+                    star.timestamp.to_rfc3339(),
+                    star.member_id,
+                    csv_field(&star.member),
+                    star.day,
+                    star.part,
+                    star.elapsed.num_seconds()
+                );
+            }
+        }
+        Format::Markdown => {
+            println!("| Time | Year | Leaderboard | Member | Star | Elapsed |");
+            println!("|---|--:|--:|---|---|--:|");
+            for star in stars {
+                println!(
+                    "| {} | {} | {} | {} | {:02}-{} | {} |",
+                    star.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    star.year,
+                    star.leaderboard,
+                    cell(&star.member),
+                    star.day,
+                    star.part,
+                    duration_string(star.elapsed)
+                );
+            }
+        }
+        Format::Html => {
+            let mut body = String::from(
+                "<table><thead><tr><th>Time</th><th>Year</th><th>Leaderboard</th>\
+                 <th>Member</th><th>Star</th><th>Elapsed</th></tr></thead><tbody>\n",
+            );
+            for star in stars {
+                writeln!(
+                    body,
+                    "<tr><td>{}</td><td class=\"n\">{}</td><td class=\"n\">{}</td><td>{}</td>\
+                     <td>{:02}-{}</td><td class=\"n\" data-v=\"{}\">{}</td></tr>",
+                    star.timestamp.format("%Y-%m-%d %H:%M:%S"),
+                    star.year,
+                    star.leaderboard,
+                    html::escape(&star.member),
+                    star.day,
+                    star.part,
+                    star.elapsed.num_seconds(),
+                    duration_string(star.elapsed)
+                )
+                .unwrap();
+            }
+            body.push_str("</tbody></table>\n");
+            print!("{}", html::page("Advent of Code query", &body));
+        }
+    }
+}
@@ -0,0 +1,288 @@
+//! What changed between a cached snapshot and a fresh fetch.
+use crate::layout;
+use crate::locale;
+use crate::render;
+use crate::scoring::Counting;
+use crate::{automatic, display_names, duration_string, Aoc, Board, Score, StarId};
+use chrono::{DateTime, Duration, Local};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum DiffFormat {
+    #[default]
+    Text,
+    Json,
+    Markdown,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NewStar {
+    pub timestamp: DateTime<Local>,
+    pub member: String,
+    pub member_id: i32,
+    pub day: u32,
+    pub star: StarId,
+    #[serde(rename = "elapsed_seconds", serialize_with = "crate::seconds")]
+    pub elapsed: Duration,
+    pub points: usize,
+    /// The member's total and rank on the fresh leaderboard.
+    pub score: usize,
+    pub rank: usize,
+}
+
+impl NewStar {
+    /// The solve time like `12:34`, or `free` for day 25's second star.
+    pub fn elapsed_string(&self) -> String {
+        if automatic(self.star.day, self.star.part) {
+            "free".to_string()
+        } else {
+            duration_string(self.elapsed)
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RankMove {
+    pub member: String,
+    /// `None` when the member was not on the previous leaderboard.
+    pub from: Option<usize>,
+    pub to: usize,
+}
+
+/// A member's total before and after their new stars.
+#[derive(Debug, Serialize)]
+pub struct ScoreChange {
+    pub member: String,
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Diff {
+    pub year: i32,
+    pub leaderboard: i32,
+    pub new_stars: Vec<NewStar>,
+    pub rank_moves: Vec<RankMove>,
+    pub score_changes: Vec<ScoreChange>,
+}
+
+impl Diff {
+    pub fn is_empty(&self) -> bool {
+        self.new_stars.is_empty() && self.rank_moves.is_empty()
+    }
+}
+
+/// The member ids behind the display names of `aoc`, which are unique on
+/// one board.
+fn ids(aoc: &Aoc) -> HashMap<String, i32> {
+    display_names(&aoc.members)
+        .into_iter()
+        .map(|(id, name)| (name, id))
+        .collect()
+}
+
+/// Compares members by id, so a renamed member's stars are not new. Points,
+/// scores and places are those of `counting`.
+pub fn diff(board: Board, old: &Aoc, new: &Aoc, counting: Counting) -> Diff {
+    let old_events = counting.scored(old);
+    let seen: HashSet<(i32, StarId)> = old_events
+        .iter()
+        .map(|(event, _)| (event.member_id, event.star))
+        .collect();
+    let events = counting.scored(new);
+    let new_ids = ids(new);
+    let new_standings = counting.standings(new, &events);
+    let id = |score: &Score| new_ids[score.member.as_str()];
+    let new_stars: Vec<NewStar> = events
+        .iter()
+        .filter(|(event, _)| !seen.contains(&(event.member_id, event.star)))
+        .map(|(event, points)| {
+            let (rank, score) = new_standings
+                .iter()
+                .find(|score| id(score) == event.member_id)
+                .map_or((0, 0), |score| (score.rank, score.points));
+            NewStar {
+                timestamp: event.timestamp,
+                member: event.member.to_string(),
+                member_id: event.member_id,
+                day: event.day(),
+                star: event.star,
+                elapsed: event.elapsed,
+                points: *points,
+                score,
+                rank,
+            }
+        })
+        .collect();
+
+    let old_ids = ids(old);
+    let old_standings: HashMap<i32, (usize, usize)> = counting
+        .standings(old, &old_events)
+        .into_iter()
+        .map(|score| (old_ids[score.member.as_str()], (score.rank, score.points)))
+        .collect();
+    let score_changes = new_standings
+        .iter()
+        .filter(|score| new_stars.iter().any(|star| star.member_id == id(score)))
+        .map(|score| ScoreChange {
+            member: score.member.clone(),
+            from: old_standings
+                .get(&id(score))
+                .map_or(0, |&(_, points)| points),
+            to: score.points,
+        })
+        .collect();
+    let rank_moves = new_standings
+        .iter()
+        .filter_map(|score| {
+            let from = old_standings.get(&id(score)).map(|&(rank, _)| rank);
+            (from != Some(score.rank)).then(|| RankMove {
+                member: score.member.clone(),
+                from,
+                to: score.rank,
+            })
+        })
+        .collect();
+
+    Diff {
+        year: board.year,
+        leaderboard: board.leaderboard,
+        new_stars,
+        rank_moves,
+        score_changes,
+    }
+}
+
+fn rank_string(rank: Option<usize>) -> String {
+    rank.map_or_else(|| "-".to_string(), |r| r.to_string())
+}
+
+pub fn print(diffs: &[Diff], format: DiffFormat) {
+    match format {
+        DiffFormat::Text => {
+            for diff in diffs {
+                println!("\nLeaderboard {} ({}):", diff.leaderboard, diff.year);
+                if diff.is_empty() {
+                    println!("  nothing new");
+                    continue;
+                }
+                for star in &diff.new_stars {
+                    println!(
+                        "  {} {}\t{} [+{}]",
+                        locale::format(&star.timestamp, "%B %e %H:%M:%S"),
+                        layout::name(&star.member),
+                        star.star,
+                        star.points
+                    );
+                }
+                for rank in &diff.rank_moves {
+                    println!(
+                        "  {} {} -> {}",
+                        layout::name(&rank.member),
+                        rank_string(rank.from),
+                        rank.to
+                    );
+                }
+            }
+        }
+        DiffFormat::Json => println!("{}", serde_json::to_string_pretty(diffs).unwrap()),
+        DiffFormat::Markdown => {
+            for diff in diffs {
+                println!("### Leaderboard {} ({})\n", diff.leaderboard, diff.year);
+                if diff.is_empty() {
+                    println!("Nothing new.\n");
+                    continue;
+                }
+                if !diff.new_stars.is_empty() {
+                    println!("| Time | Member | Star | Points |");
+                    println!("|---|---|---|---:|");
+                    for star in &diff.new_stars {
+                        println!(
+                            "| {} | {} | {} | +{} |",
+                            locale::format(&star.timestamp, "%B %e %H:%M:%S"),
+                            render::cell(&star.member),
+                            star.star,
+                            star.points
+                        );
+                    }
+                    println!();
+                }
+                if !diff.rank_moves.is_empty() {
+                    println!("| Member | From | To |");
+                    println!("|---|---:|---:|");
+                    for rank in &diff.rank_moves {
+                        println!(
+                            "| {} | {} | {} |",
+                            render::cell(&rank.member),
+                            rank_string(rank.from),
+                            rank.to
+                        );
+                    }
+                    println!();
+                }
+            }
+        }
+    }
+}
+
+/// Only the new stars and the score changes they caused.
+pub fn print_new_stars(diffs: &[Diff]) {
+    for diff in diffs {
+        println!("\nLeaderboard {} ({}):", diff.leaderboard, diff.year);
+        if diff.new_stars.is_empty() {
+            println!("  no new stars");
+            continue;
+        }
+        for star in &diff.new_stars {
+            println!(
+                "  {} {}\t{} [+{}] ({})",
+                locale::format(&star.timestamp, "%B %e %H:%M:%S"),
+                layout::name(&star.member),
+                star.star,
+                star.points,
+                star.elapsed_string()
+            );
+        }
+        println!();
+        for change in &diff.score_changes {
+            println!(
+                "  {} {} -> {} (+{})",
+                layout::name(&change.member),
+                change.from,
+                change.to,
+                change.to.saturating_sub(change.from)
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sample;
+
+    #[test]
+    fn renamed_members_have_no_new_stars() {
+        let old = sample::leaderboard(2024, sample::SEED);
+        let mut new = old.clone();
+        for member in new.members.values_mut() {
+            // Bob becomes a second Alice, so both gain an #id suffix.
+            if member.name.as_deref() == Some("Bob") {
+                member.name = Some("Alice".into());
+            }
+            if member.name.is_none() {
+                member.name = Some("Zed".into());
+            }
+        }
+        let board = Board {
+            year: 2024,
+            leaderboard: 100_001,
+        };
+        let diff = diff(board, &old, &new, Counting::default());
+        assert!(diff.new_stars.is_empty());
+        assert!(diff.rank_moves.is_empty());
+        assert!(diff.score_changes.is_empty());
+    }
+}
@@ -0,0 +1,71 @@
+//! The day's report by email.
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::fetch::secret;
+use crate::html;
+use crate::locale;
+use crate::render::{self, LeaderboardReport};
+use crate::runtime;
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+fn mail_error(e: impl std::fmt::Display) -> Error {
+    Error::Mail(e.to_string())
+}
+
+/// [`send_async`] on the shared runtime.
+///
+/// # Errors
+///
+/// As [`send_async`].
+pub fn send(config: &Config, reports: &[LeaderboardReport]) -> Result<()> {
+    runtime::block_on(send_async(config, reports))
+}
+
+/// Emails `reports` to the recipients in the `[digest]` section, as
+/// Markdown text with an HTML alternative.
+///
+/// # Errors
+///
+/// When the config has no `[digest]` section, an address is invalid, the
+/// `smtp_password` secret is missing or sending fails.
+pub async fn send_async(config: &Config, reports: &[LeaderboardReport]) -> Result<()> {
+    let Some(digest) = &config.digest else {
+        return Err(Error::Usage {
+            message: "digest needs a [digest] section in the config".into(),
+        });
+    };
+    let from: Mailbox = digest.from.parse().map_err(mail_error)?;
+    let mut builder = Message::builder().from(from).subject(format!(
+        "Advent of Code: {}",
+        locale::format(&chrono::Local::now(), "%B %e")
+    ));
+    for to in &digest.to {
+        builder = builder.to(to.parse().map_err(mail_error)?);
+    }
+    let text: Vec<String> = reports.iter().map(render::markdown).collect();
+    let message = builder
+        .multipart(MultiPart::alternative_plain_html(
+            text.join("\n"),
+            html::report(reports),
+        ))
+        .map_err(mail_error)?;
+    let mut transport = if digest.starttls {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&digest.smtp_server)
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::relay(&digest.smtp_server)
+    }
+    .map_err(mail_error)?;
+    if let Some(port) = digest.port {
+        transport = transport.port(port);
+    }
+    if let Some(username) = &digest.username {
+        transport = transport.credentials(Credentials::new(
+            username.clone(),
+            secret(config, "smtp_password")?,
+        ));
+    }
+    transport.build().send(message).await.map_err(mail_error)?;
+    Ok(())
+}
@@ -0,0 +1,129 @@
+//! Ratings from playing every day as a round robin between all members.
+use crate::{automatic, display_names, rank, Aoc, Member, Score};
+use itertools::Itertools;
+use std::cmp::Reverse;
+
+/// Where every member starts.
+const START: f64 = 1500.0;
+/// The most a rating can move in one day.
+const K: f64 = 32.0;
+
+/// How `member` did on `day`, better results first: more stars, then the
+/// earlier last star. `None` without a star that day. Day 25's free star
+/// does not count.
+fn result(member: &Member, day: u32) -> Option<(Reverse<usize>, i64)> {
+    let stars: Vec<i64> = member
+        .completion_day_level
+        .get(&day)?
+        .iter()
+        .filter(|(part, _)| !automatic(day, **part))
+        .map(|(_, star)| star.get_star_ts)
+        .collect();
+    let last = stars.iter().max()?;
+    Some((Reverse(stars.len()), *last))
+}
+
+/// Every member's Elo rating after the days played so far. On each day every
+/// member plays every other one, winning with the better result; a member
+/// without a star that day loses to everyone who has one.
+#[allow(
+    clippy::cast_precision_loss,
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss
+)]
+pub fn ratings(aoc: &Aoc) -> Vec<Score> {
+    let names = display_names(&aoc.members);
+    let members: Vec<&Member> = aoc.members.values().sorted_by_key(|m| m.id).collect();
+    let mut ratings = vec![START; members.len()];
+    // Spread the day's games over everyone's K, so a day counts the same
+    // however big the board is.
+    let k = K / members.len().saturating_sub(1).max(1) as f64;
+    let last_day = members
+        .iter()
+        .flat_map(|member| member.completion_day_level.keys())
+        .max()
+        .copied()
+        .unwrap_or(0);
+    for day in 1..=last_day {
+        let results: Vec<_> = members.iter().map(|member| result(member, day)).collect();
+        let mut changes = vec![0.0; members.len()];
+        for (a, b) in (0..members.len()).tuple_combinations() {
+            let score = match (results[a], results[b]) {
+                (None, None) => continue,
+                (Some(_), None) => 1.0,
+                (None, Some(_)) => 0.0,
+                (Some(x), Some(y)) if x < y => 1.0,
+                (Some(x), Some(y)) if y < x => 0.0,
+                (Some(_), Some(_)) => 0.5,
+            };
+            let expected = 1.0 / (1.0 + 10f64.powf((ratings[b] - ratings[a]) / 400.0));
+            changes[a] += k * (score - expected);
+            changes[b] -= k * (score - expected);
+        }
+        for (rating, change) in ratings.iter_mut().zip(changes) {
+            *rating += change;
+        }
+    }
+    let mut standings: Vec<Score> = members
+        .iter()
+        .zip(ratings)
+        .map(|(member, rating)| Score {
+            rank: 0,
+            member: names[&member.id].clone(),
+            points: rating.round().max(0.0) as usize,
+            percent: None,
+            local_score: None,
+        })
+        .sorted_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then_with(|| a.member.cmp(&b.member))
+        })
+        .collect();
+    rank(&mut standings, |a, b| a.points == b.points);
+    standings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{board, member};
+    use crate::unlock;
+
+    /// `stars` stars on each of `days` of 2024, taken one minute after the
+    /// unlock.
+    fn stars(days: &[u32], stars: u32) -> Vec<(u32, u32, i64)> {
+        days.iter()
+            .flat_map(|&day| {
+                let taken = unlock(2024, day).timestamp() + 60;
+                (1..=stars).map(move |part| (day, part, taken))
+            })
+            .collect()
+    }
+
+    fn points(standings: &[Score]) -> Vec<(&str, usize)> {
+        standings
+            .iter()
+            .map(|score| (score.member.as_str(), score.points))
+            .collect()
+    }
+
+    #[test]
+    fn the_winner_gains_what_the_loser_loses() {
+        let aoc = board([
+            member(1, "Alice", &stars(&[1], 2)),
+            member(2, "Bob", &stars(&[1], 1)),
+        ]);
+        assert_eq!(points(&ratings(&aoc)), [("Alice", 1516), ("Bob", 1484)]);
+    }
+
+    #[test]
+    fn a_day_without_stars_is_not_played() {
+        // Played as a draw, day 2 would pull the ratings to 1529 and 1471.
+        let aoc = board([
+            member(1, "Alice", &stars(&[1, 3], 2)),
+            member(2, "Bob", &stars(&[1, 3], 1)),
+        ]);
+        assert_eq!(points(&ratings(&aoc)), [("Alice", 1531), ("Bob", 1469)]);
+    }
+}
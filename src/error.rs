@@ -0,0 +1,139 @@
+//! Everything that can go wrong, with the exit code the binary uses for it.
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{message}")]
+    Usage { message: String },
+    #[error("failed to read {}: {source}", path.display())]
+    Config {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to parse {}: {source}", path.display())]
+    ConfigSyntax {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("failed to read cache {}: {source}", path.display())]
+    CacheRead {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to write {}: {source}", path.display())]
+    Write {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("failed to render template {}: {message}", path.display())]
+    Template { path: PathBuf, message: String },
+    #[error("failed to draw {}: {message}", path.display())]
+    Chart { path: PathBuf, message: String },
+    #[error("failed to read leaderboard JSON {}: {source}", path.display())]
+    Input {
+        path: PathBuf,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error(
+        "failed to load the secret '{name}': {message}\n\
+         Set it with 'ssclient -s {} -k {} set {name}' ('cargo install ssclient').",
+        store.display(),
+        key.display()
+    )]
+    Secrets {
+        name: String,
+        message: String,
+        store: PathBuf,
+        key: PathBuf,
+    },
+    #[error(
+        "fetch failed with {0}, cookie probably outdated.\n\
+         Set a new cookie with 'ssclient set session <COOKIE>' ('cargo install ssclient')."
+    )]
+    Unauthorized(reqwest::StatusCode),
+    #[error("adventofcode.com answered {0}, try again later")]
+    Unavailable(reqwest::StatusCode),
+    #[error("adventofcode.com answered {0}")]
+    Status(reqwest::StatusCode),
+    #[error(
+        "leaderboard {leaderboard} ({year}) does not exist.\n\
+         Check the id, the number at the end of the leaderboard's URL."
+    )]
+    NoSuchLeaderboard { year: i32, leaderboard: i32 },
+    #[error(
+        "leaderboard {leaderboard} ({year}) returned no leaderboard data.\n\
+         Check the id and that your account has joined that leaderboard."
+    )]
+    NoAccess { year: i32, leaderboard: i32 },
+    #[error("leaderboard {leaderboard} ({year}) is not leaderboard JSON: {source}")]
+    Parse {
+        year: i32,
+        leaderboard: i32,
+        source: serde_json::Error,
+    },
+    #[error(
+        "leaderboard {leaderboard} ({year}) was requested less than 15 minutes ago and is \
+         not cached, try again after {until}"
+    )]
+    Throttled {
+        year: i32,
+        leaderboard: i32,
+        until: String,
+    },
+    #[error("failed to listen on {addr}: {source}")]
+    Serve {
+        addr: String,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+    #[error("failed to send email: {0}")]
+    Mail(String),
+    #[error("command '{command}' failed: {message}")]
+    Hook { command: String, message: String },
+    #[error("failed to show a desktop notification: {0}")]
+    Desktop(String),
+    #[cfg(feature = "sqlite")]
+    #[error("database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("terminal error: {0}")]
+    Terminal(std::io::Error),
+    #[error("request to adventofcode.com failed: {0}")]
+    Http(#[from] reqwest::Error),
+    #[error("a leaderboard fetch did not finish: {0}")]
+    Task(tokio::task::JoinError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Error {
+    /// 2 for bad arguments (like clap), 3 for configuration, 4 for local
+    /// files, 5 for the session cookie, 6 for leaderboard access, 7 for
+    /// network trouble or an answer that is not a leaderboard and 1 for the
+    /// terminal, the server, hooks, desktop
+    /// notifications or a fetch that did not finish.
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            Error::Usage { .. } => 2,
+            Error::Config { .. } | Error::ConfigSyntax { .. } => 3,
+            Error::CacheRead { .. }
+            | Error::Write { .. }
+            | Error::Template { .. }
+            | Error::Chart { .. }
+            | Error::Input { .. } => 4,
+            #[cfg(feature = "sqlite")]
+            Error::Database(_) => 4,
+            Error::Secrets { .. } | Error::Unauthorized(_) => 5,
+            Error::NoAccess { .. } | Error::NoSuchLeaderboard { .. } => 6,
+            Error::Http(_)
+            | Error::Unavailable(_)
+            | Error::Status(_)
+            | Error::Parse { .. }
+            | Error::Throttled { .. }
+            | Error::Mail(_) => 7,
+            Error::Terminal(_)
+            | Error::Serve { .. }
+            | Error::Hook { .. }
+            | Error::Desktop(_)
+            | Error::Task(_) => 1,
+        }
+    }
+}
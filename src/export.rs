@@ -0,0 +1,90 @@
+//! Raw data for plotting elsewhere.
+use crate::error::{Error, Result};
+use crate::scoring::Counting;
+use crate::Aoc;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Quotes a CSV field when it contains a separator, quote or newline.
+pub fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// One row per star in long format, replaying the points of `counting` so
+/// every row carries the member's score and rank right after that star;
+/// `official` and `elo` rank by something else and export their positional
+/// points. `members`
+/// picks whose rows to write on each board, everyone's when it gives `None`;
+/// ranks still count the whole board.
+///
+/// # Errors
+///
+/// When `members` fails.
+pub fn charts_data(
+    boards: &[Aoc],
+    counting: Counting,
+    members: impl Fn(&Aoc) -> Result<Option<HashSet<i32>>>,
+) -> Result<String> {
+    let mut csv = String::from(
+        "leaderboard,member,day,part,timestamp,elapsed_seconds,cumulative_score,rank_at_time\n",
+    );
+    for aoc in boards {
+        let members = members(aoc)?;
+        let mut totals: HashMap<i32, usize> = HashMap::new();
+        for (event, points) in counting.scored(aoc) {
+            let total = *totals
+                .entry(event.member_id)
+                .and_modify(|t| *t += points)
+                .or_insert(points);
+            if members
+                .as_ref()
+                .is_some_and(|members| !members.contains(&event.member_id))
+            {
+                continue;
+            }
+            let rank = 1 + totals.values().filter(|t| **t > total).count();
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{total},{rank}",
+                aoc.owner_id,
+                csv_field(&event.member),
+                event.day(),
+                event.part(),
+                event.timestamp.to_rfc3339(),
+                event.elapsed.num_seconds(),
+            )
+            .unwrap();
+        }
+    }
+    Ok(csv)
+}
+
+/// Writes [`charts_data`] to `path`, creating its directory.
+///
+/// # Errors
+///
+/// When `members` fails or the file cannot be written.
+pub fn write_charts_data(
+    path: &Path,
+    boards: &[Aoc],
+    counting: Counting,
+    members: impl Fn(&Aoc) -> Result<Option<HashSet<i32>>>,
+) -> Result<()> {
+    let error = |source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    };
+    let csv = charts_data(boards, counting, members)?;
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(error)?;
+    }
+    std::fs::write(path, csv).map_err(error)
+}
@@ -0,0 +1,635 @@
+//! Leaderboard JSON from adventofcode.com, kept in the [`cache`].
+use crate::cache::{self, Cache, CacheEntry, Validators};
+use crate::config::{Config, Retry};
+use crate::error::{Error, Result};
+use crate::throttle::{self, Throttle};
+use crate::warnings::{Category, Warnings};
+use crate::{atomic, history, progress, runtime, Aoc, Board};
+use chrono::{Duration, Local};
+use itertools::Itertools;
+use reqwest::{
+    header::{
+        HeaderMap, HeaderValue, ACCEPT, COOKIE, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+        LAST_MODIFIED, LOCATION, USER_AGENT,
+    },
+    redirect, Client, Proxy, StatusCode,
+};
+use securestore::{KeySource, SecretsManager};
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::Path;
+use std::time::Duration as StdDuration;
+use tracing::{debug, info, trace};
+
+/// A leaderboard as fetched, or word that the cached one is still current.
+enum Fetched {
+    Changed(Aoc, Validators),
+    Unchanged,
+}
+
+/// Reads `name` from the secrets store.
+///
+/// # Errors
+///
+/// When the store cannot be opened or has no such secret.
+pub fn secret(config: &Config, name: &str) -> Result<String> {
+    SecretsManager::load(&config.secrets, KeySource::Path(&config.secrets_key))
+        .and_then(|sman| sman.get(name))
+        .map_err(|e| Error::Secrets {
+            name: name.to_string(),
+            message: e.to_string(),
+            store: config.secrets.clone(),
+            key: config.secrets_key.clone(),
+        })
+}
+
+/// # Errors
+///
+/// When the cache is unreadable, the session cookie is missing or rejected,
+/// the board is not accessible or the request fails.
+pub fn get_json(config: &Config, board: Board, warnings: &mut Warnings) -> Result<Aoc> {
+    Ok(get_all(config, &[board], warnings)?.remove(0))
+}
+
+/// Gets `boards` in order, from the cache while it is fresh and otherwise
+/// from adventofcode.com, fetching them all at the same time over one
+/// client. Whatever was fetched is cached even when another board fails.
+/// A corrupt cache and retried requests are noted in `warnings`. Waits for
+/// [`get_all_async`] on the shared runtime.
+///
+/// # Errors
+///
+/// When the cache is unreadable, the session cookie is missing or rejected,
+/// a board is not accessible or a request fails.
+pub fn get_all(config: &Config, boards: &[Board], warnings: &mut Warnings) -> Result<Vec<Aoc>> {
+    runtime::block_on(get_all_async(config, boards, warnings))
+}
+
+/// [`get_all`] for callers already on the runtime.
+///
+/// # Errors
+///
+/// As for [`get_all`].
+pub async fn get_all_async(
+    config: &Config,
+    boards: &[Board],
+    warnings: &mut Warnings,
+) -> Result<Vec<Aoc>> {
+    let boards = update(config, boards, warnings).await?;
+    Ok(boards.into_iter().map(|(aoc, _)| aoc).collect())
+}
+
+/// What the cache held for a board before [`get_all_since`] brought it up
+/// to date.
+#[derive(Clone)]
+pub enum Previous {
+    /// Nothing, the board was not cached.
+    Missing,
+    /// The board as it is now, cached and fresh or unchanged.
+    Same,
+    /// The copy that was replaced.
+    Older(Aoc),
+}
+
+/// [`get_all`] with what the cache held for each board before, to tell
+/// what changed without reading the cache twice.
+///
+/// # Errors
+///
+/// As for [`get_all`].
+pub fn get_all_since(
+    config: &Config,
+    boards: &[Board],
+    warnings: &mut Warnings,
+) -> Result<Vec<(Aoc, Previous)>> {
+    runtime::block_on(update(config, boards, warnings))
+}
+
+async fn update(
+    config: &Config,
+    boards: &[Board],
+    warnings: &mut Warnings,
+) -> Result<Vec<(Aoc, Previous)>> {
+    // Another run fetching the same boards waits, then finds them cached.
+    let _lock = atomic::lock(&config.cache)?;
+    let mut cache = cache::read(config, warnings)?;
+    let mut throttle = Throttle::load(config, warnings)?;
+    let (requests, mut failure) = to_fetch(config, &cache, &throttle, boards);
+    let mut previous = HashMap::new();
+    if !requests.is_empty() {
+        let fetched = fetch_all(config, &mut throttle, &requests, warnings).await?;
+        for ((board, _), fetched) in requests.iter().zip(fetched) {
+            let board = *board;
+            match fetched {
+                Ok(Fetched::Unchanged) => {
+                    debug!("leaderboard {} is unchanged", board.leaderboard);
+                    if let Some(entry) = cache.get_mut(&board.cache_key()) {
+                        entry.timestamp = Local::now();
+                    }
+                }
+                Ok(Fetched::Changed(aoc, validators)) => {
+                    let older = store(config, &mut cache, board, aoc, validators, warnings);
+                    previous.insert(board.cache_key(), older);
+                }
+                Err(e) => failure = failure.or(Some(e)),
+            }
+        }
+        cache::write(config, &cache)?;
+        debug!("cached in {}", config.cache.display());
+    }
+    if let Some(e) = failure {
+        return Err(e);
+    }
+    Ok(take(cache, &mut previous, boards))
+}
+
+/// Puts a freshly fetched `aoc` in `cache`, returning what it replaced, and
+/// keeps it in the history and database. Those only warn when they fail, so
+/// the fetch is cached all the same rather than thrown away and throttled.
+fn store(
+    config: &Config,
+    cache: &mut Cache,
+    board: Board,
+    aoc: Aoc,
+    validators: Validators,
+    warnings: &mut Warnings,
+) -> Previous {
+    if let Err(e) = history::record(config, board, &aoc) {
+        warnings.push(Category::Cache, format!("history not kept: {e}"));
+    }
+    #[cfg(feature = "sqlite")]
+    if let Err(e) = crate::db::record(config, board, &aoc) {
+        warnings.push(Category::Cache, format!("not stored in the database: {e}"));
+    }
+    let entry = CacheEntry {
+        timestamp: Local::now(),
+        data: aoc,
+        validators,
+    };
+    match cache.insert(board.cache_key(), entry) {
+        Some(older) => Previous::Older(older.data),
+        None => Previous::Missing,
+    }
+}
+
+/// The entries for `boards` out of `cache`, each with what it replaced,
+/// moved rather than copied unless a board is asked for twice.
+fn take(
+    mut cache: Cache,
+    previous: &mut HashMap<String, Previous>,
+    boards: &[Board],
+) -> Vec<(Aoc, Previous)> {
+    boards
+        .iter()
+        .enumerate()
+        .map(|(i, board)| {
+            let key = board.cache_key();
+            if boards[i + 1..].iter().any(|later| later.cache_key() == key) {
+                let before = previous.get(&key).cloned();
+                (cache[&key].data.clone(), before.unwrap_or(Previous::Same))
+            } else {
+                let before = previous.remove(&key);
+                (
+                    cache.remove(&key).unwrap().data,
+                    before.unwrap_or(Previous::Same),
+                )
+            }
+        })
+        .collect()
+}
+
+/// The boards to request, once each and with the validators of their cached
+/// copy, and the error for the first board that is neither fresh in the
+/// cache nor allowed by `throttle`.
+fn to_fetch(
+    config: &Config,
+    cache: &Cache,
+    throttle: &Throttle,
+    boards: &[Board],
+) -> (Vec<(Board, Validators)>, Option<Error>) {
+    let mut requests: Vec<(Board, Validators)> = Vec::new();
+    let mut failure = None;
+    for &board in boards {
+        let cached = cache.get(&board.cache_key());
+        match cached {
+            Some(entry)
+                if entry.timestamp + Duration::minutes(config.cache_ttl(board.leaderboard))
+                    > Local::now() =>
+            {
+                info!(
+                    "using the cached leaderboard {} ({}) from {}",
+                    board.leaderboard,
+                    board.year,
+                    entry.timestamp.format("%H:%M:%S")
+                );
+                continue;
+            }
+            Some(entry) => debug!(
+                "the cached leaderboard {} ({}) from {} is stale",
+                board.leaderboard,
+                board.year,
+                entry.timestamp.format("%H:%M:%S")
+            ),
+            None => {}
+        }
+        if let Some(until) = throttle.until(board) {
+            match cached {
+                Some(entry) => info!(
+                    "leaderboard {} ({}) was requested less than {} minutes ago, \
+                     using the cached copy from {}",
+                    board.leaderboard,
+                    board.year,
+                    throttle::MINUTES,
+                    entry.timestamp.format("%H:%M:%S")
+                ),
+                None => {
+                    failure = failure.or(Some(Error::Throttled {
+                        year: board.year,
+                        leaderboard: board.leaderboard,
+                        until: until.format("%H:%M:%S").to_string(),
+                    }));
+                }
+            }
+            continue;
+        }
+        if !requests
+            .iter()
+            .any(|(b, _)| b.cache_key() == board.cache_key())
+        {
+            let validators = cached
+                .map(|entry| entry.validators.clone())
+                .unwrap_or_default();
+            requests.push((board, validators));
+        }
+    }
+    (requests, failure)
+}
+
+/// The cached copies of `boards`, to show while they are fetched again: when
+/// every board is cached, none is older than `max_age` and some are due for
+/// a refresh.
+///
+/// # Errors
+///
+/// When the cache is unreadable.
+pub fn stale_copies(
+    config: &Config,
+    boards: &[Board],
+    max_age: Duration,
+    warnings: &mut Warnings,
+) -> Result<Option<Vec<Aoc>>> {
+    let cache = cache::read(config, warnings)?;
+    let now = Local::now();
+    let Some(entries) = boards
+        .iter()
+        .map(|board| cache.get(&board.cache_key()))
+        .collect::<Option<Vec<_>>>()
+    else {
+        return Ok(None);
+    };
+    let fresh = boards.iter().zip(&entries).all(|(board, entry)| {
+        entry.timestamp + Duration::minutes(config.cache_ttl(board.leaderboard)) > now
+    });
+    if fresh || entries.iter().any(|entry| entry.timestamp + max_age < now) {
+        return Ok(None);
+    }
+    let oldest = entries.iter().map(|entry| entry.timestamp).min().unwrap();
+    info!(
+        "showing the cached leaderboards from {}, fetching them after the report",
+        oldest.format("%H:%M:%S")
+    );
+    let boards = take(cache, &mut HashMap::new(), boards);
+    Ok(Some(boards.into_iter().map(|(aoc, _)| aoc).collect()))
+}
+
+/// Fetches `boards` from adventofcode.com as a task each, behind one
+/// spinner, noting the requests in `throttle` and the retries in
+/// `warnings`. Boards with validators are only sent if they changed.
+async fn fetch_all(
+    config: &Config,
+    throttle: &mut Throttle,
+    requests: &[(Board, Validators)],
+    warnings: &mut Warnings,
+) -> Result<Vec<Result<Fetched>>> {
+    let boards: Vec<_> = requests.iter().map(|(board, _)| *board).collect();
+    let session = secret(config, "session")?;
+    let mut headers = HeaderMap::new();
+    let cookie = format!("session={session};")
+        .parse()
+        .map_err(|_| Error::Secrets {
+            name: "session".into(),
+            message: "not a valid header value".into(),
+            store: config.secrets.clone(),
+            key: config.secrets_key.clone(),
+        })?;
+    headers.insert(COOKIE, cookie);
+    headers.insert(ACCEPT, "application/json".parse().unwrap());
+    headers.insert(USER_AGENT, user_agent(config)?);
+    let mut client = Client::builder()
+        .default_headers(headers)
+        .timeout(StdDuration::from_secs(config.http.timeout_secs))
+        .connect_timeout(StdDuration::from_secs(config.http.connect_timeout_secs))
+        // A rejected cookie is redirected to the login page, so a redirect
+        // is an answer of its own.
+        .redirect(redirect::Policy::none());
+    if let Some(proxy) = &config.http.proxy {
+        client = client.proxy(Proxy::all(proxy)?);
+    }
+    let client = client.build()?;
+    throttle.record(&boards)?;
+    let message = format!(
+        "fetching leaderboard{} {}",
+        if boards.len() == 1 { "" } else { "s" },
+        boards
+            .iter()
+            .map(|board| format!("{} ({})", board.leaderboard, board.year))
+            .join(", ")
+    );
+    let _spinner = progress::start(message);
+    let tasks: Vec<_> = requests
+        .iter()
+        .map(|(board, validators)| {
+            runtime::runtime().spawn(download(
+                client.clone(),
+                *board,
+                validators.clone(),
+                config.retry,
+            ))
+        })
+        .collect();
+    let mut fetched = Vec::with_capacity(tasks.len());
+    for task in tasks {
+        let (result, retries) = task.await.map_err(Error::Task)?;
+        for retry in retries {
+            warnings.push(Category::Fetch, retry);
+        }
+        fetched.push(result);
+    }
+    Ok(fetched)
+}
+
+/// The start of the User-Agent: the tool and where to find it.
+const AGENT: &str = concat!(
+    env!("CARGO_PKG_NAME"),
+    "/",
+    env!("CARGO_PKG_VERSION"),
+    " (+",
+    env!("CARGO_PKG_REPOSITORY")
+);
+
+/// Names the tool, where to find it and, if configured, who runs it, as
+/// adventofcode.com asks of automated requests.
+fn user_agent(config: &Config) -> Result<HeaderValue> {
+    let agent = match &config.http.contact {
+        Some(contact) => format!("{AGENT}; {contact})"),
+        None => format!("{AGENT})"),
+    };
+    agent.parse().map_err(|_| Error::Usage {
+        message: "the contact cannot go in a User-Agent header".into(),
+    })
+}
+
+/// Gets one leaderboard unless `validators` show it unchanged, trying again
+/// after a server error or a failed connection, but not after the cookie is
+/// rejected. Also returns a line on each retry, for the warnings.
+async fn download(
+    client: Client,
+    board: Board,
+    validators: Validators,
+    retry: Retry,
+) -> (Result<Fetched>, Vec<String>) {
+    let mut retries = Vec::new();
+    let fetched = try_download(client, board, validators, retry, &mut retries).await;
+    (fetched, retries)
+}
+
+async fn try_download(
+    client: Client,
+    board: Board,
+    validators: Validators,
+    retry: Retry,
+    retries: &mut Vec<String>,
+) -> Result<Fetched> {
+    let url = format!(
+        "https://adventofcode.com/{}/leaderboard/private/view/{}.json",
+        board.year, board.leaderboard
+    );
+    let mut attempt = 1;
+    let response = loop {
+        match get(&client, &url, board, &validators).await {
+            Err(e) if attempt < retry.attempts && retryable(&e) => {
+                let wait = backoff(retry, attempt);
+                let retrying = format!(
+                    "{e}; trying leaderboard {} again in {:.1}s",
+                    board.leaderboard,
+                    wait.as_secs_f64()
+                );
+                info!("{retrying}");
+                retries.push(retrying);
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            response => break response?,
+        }
+    };
+    let Some((text, validators)) = response else {
+        return Ok(Fetched::Unchanged);
+    };
+    trace!("{} bytes of leaderboard JSON", text.len());
+    Ok(Fetched::Changed(parse(&text, board)?, validators))
+}
+
+/// The leaderboard in `text`. A web page instead of JSON means the account
+/// may not see the board.
+fn parse(text: &str, board: Board) -> Result<Aoc> {
+    if text.trim_start().starts_with('<') {
+        return Err(Error::NoAccess {
+            year: board.year,
+            leaderboard: board.leaderboard,
+        });
+    }
+    serde_json::from_str(text).map_err(|source| Error::Parse {
+        year: board.year,
+        leaderboard: board.leaderboard,
+        source,
+    })
+}
+
+/// The body and its validators, or `None` when the server says the version
+/// `validators` describe is still current.
+async fn get(
+    client: &Client,
+    url: &str,
+    board: Board,
+    validators: &Validators,
+) -> Result<Option<(String, Validators)>> {
+    debug!("GET {url}");
+    let mut request = client.get(url);
+    if let Some(etag) = &validators.etag {
+        request = request.header(IF_NONE_MATCH, etag);
+    }
+    if let Some(last_modified) = &validators.last_modified {
+        request = request.header(IF_MODIFIED_SINCE, last_modified);
+    }
+    let res = request.send().await?;
+    debug!("{} for leaderboard {}", res.status(), board.leaderboard);
+    let header = |name| {
+        res.headers()
+            .get(name)
+            .and_then(|value: &HeaderValue| value.to_str().ok())
+            .map(String::from)
+    };
+    match res.status() {
+        StatusCode::NOT_MODIFIED => Ok(None),
+        StatusCode::OK => {
+            let validators = Validators {
+                etag: header(ETAG),
+                last_modified: header(LAST_MODIFIED),
+            };
+            Ok(Some((res.text().await?, validators)))
+        }
+        status => Err(refused(status, header(LOCATION).as_deref(), board)),
+    }
+}
+
+/// The error for a leaderboard request answered with `status`, redirected
+/// to `location` if given. Only a redirect to the login page blames the
+/// cookie; others, like to the list of private leaderboards, mean the
+/// account has not joined the board.
+fn refused(status: StatusCode, location: Option<&str>, board: Board) -> Error {
+    match status {
+        StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+            Error::Unauthorized(status)
+        }
+        status
+            if status.is_redirection()
+                && location.is_some_and(|l| l.contains("/auth") || l.contains("login")) =>
+        {
+            Error::Unauthorized(status)
+        }
+        status if status.is_redirection() => Error::NoAccess {
+            year: board.year,
+            leaderboard: board.leaderboard,
+        },
+        StatusCode::NOT_FOUND => Error::NoSuchLeaderboard {
+            year: board.year,
+            leaderboard: board.leaderboard,
+        },
+        status if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS => {
+            Error::Unavailable(status)
+        }
+        status => Error::Status(status),
+    }
+}
+
+/// Whether trying again might work: not for a rejected cookie or a board
+/// that is not there.
+fn retryable(e: &Error) -> bool {
+    match e {
+        Error::Unavailable(_) => true,
+        Error::Http(e) => e.is_connect() || e.is_timeout() || e.is_request() || e.is_body(),
+        _ => false,
+    }
+}
+
+/// The wait before try `attempt + 1`: the first delay doubled for every try
+/// so far, up to a minute, with up to half of it random so that retries of
+/// several boards do not line up.
+fn backoff(retry: Retry, attempt: u32) -> StdDuration {
+    let delay = retry
+        .delay_ms
+        .saturating_mul(1 << (attempt - 1).min(16))
+        .min(60_000);
+    StdDuration::from_millis(delay - fastrand::u64(0..=delay / 2))
+}
+
+/// Reads leaderboard JSON saved from adventofcode.com.
+///
+/// # Errors
+///
+/// When the file cannot be read or is not leaderboard JSON.
+pub fn read_leaderboard(path: &Path) -> Result<Aoc> {
+    let input = |source| Error::Input {
+        path: path.to_path_buf(),
+        source,
+    };
+    let text = read_to_string(path).map_err(|e| input(e.into()))?;
+    serde_json::from_str(&text).map_err(|e| input(e.into()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{board, member};
+
+    const BOARD: Board = Board {
+        year: 2024,
+        leaderboard: 1,
+    };
+
+    #[test]
+    fn only_a_rejected_cookie_is_unauthorized() {
+        let status = |code: u16| refused(StatusCode::from_u16(code).unwrap(), None, BOARD);
+        for code in [400, 401, 403] {
+            assert!(matches!(status(code), Error::Unauthorized(_)), "{code}");
+        }
+        let redirect = |location| refused(StatusCode::FOUND, Some(location), BOARD);
+        assert!(matches!(redirect("/auth/login"), Error::Unauthorized(_)));
+        assert!(matches!(
+            redirect("/2024/leaderboard/private"),
+            Error::NoAccess { leaderboard: 1, .. }
+        ));
+        assert!(matches!(status(302), Error::NoAccess { .. }));
+        assert!(matches!(
+            status(404),
+            Error::NoSuchLeaderboard { leaderboard: 1, .. }
+        ));
+        assert!(matches!(status(429), Error::Unavailable(_)));
+        assert!(matches!(status(503), Error::Unavailable(_)));
+        assert!(matches!(status(410), Error::Status(_)));
+    }
+
+    #[test]
+    fn a_fetch_is_cached_when_its_history_cannot_be_kept() {
+        let dir = std::env::temp_dir().join(format!("aoc-history-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        // A file where the history directory should be.
+        let blocked = dir.join("history");
+        std::fs::write(&blocked, "").unwrap();
+        let config = Config {
+            history: Some(crate::config::History {
+                dir: blocked,
+                keep: None,
+                max_days: None,
+            }),
+            ..Config::default()
+        };
+        let mut cache = Cache::new();
+        let mut warnings = Warnings::default();
+        let aoc = board([member(1, "Alice", &[(1, 1, 100)])]);
+        let previous = store(
+            &config,
+            &mut cache,
+            BOARD,
+            aoc,
+            Validators::default(),
+            &mut warnings,
+        );
+        assert!(matches!(previous, Previous::Missing));
+        assert!(cache.contains_key(&BOARD.cache_key()));
+        assert_eq!(warnings.collected()[0].category, Category::Cache);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn only_a_web_page_means_no_access() {
+        assert!(matches!(
+            parse("<!DOCTYPE html>\n<html>", BOARD),
+            Err(Error::NoAccess { .. })
+        ));
+        assert!(matches!(
+            parse(r#"{"event": "2024", "owner_id": 1, "memb"#, BOARD),
+            Err(Error::Parse { leaderboard: 1, .. })
+        ));
+    }
+}
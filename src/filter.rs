@@ -0,0 +1,281 @@
+//! Selecting members by name, or by `name#id` when names are shared, or by
+//! a pattern matching either, and the filters that pick which events of the
+//! timeline to show.
+use crate::config::MemberLists;
+use crate::error::{Error, Result};
+use crate::warnings::{Category, Warnings};
+use crate::{display_name, replay, Aoc, Member, Report};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
+use regex::{Regex, RegexBuilder};
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
+use std::str::FromStr;
+
+/// What to do when a bare name or a pattern matches more than one member.
+#[derive(Clone, Copy, Debug, Default, ValueEnum)]
+pub enum Ambiguous {
+    /// Select every member that matches, with a note for a shared name.
+    #[default]
+    All,
+    /// Refuse and ask for `name#id`.
+    Error,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MemberSpec {
+    pub name: String,
+    pub id: Option<i32>,
+    /// The text as given, tried as a pattern when no name matches.
+    pub pattern: String,
+}
+
+impl FromStr for MemberSpec {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match s.rsplit_once('#') {
+            Some((name, id)) if id.parse::<i32>().is_ok() => MemberSpec {
+                name: name.to_string(),
+                id: id.parse().ok(),
+                pattern: s.to_string(),
+            },
+            _ => MemberSpec {
+                name: s.to_string(),
+                id: None,
+                pattern: s.to_string(),
+            },
+        })
+    }
+}
+
+impl MemberSpec {
+    fn matches(&self, member: &Member) -> bool {
+        let name = member.name.as_deref().unwrap_or("Anonymous");
+        match self.id {
+            Some(id) => member.id == id && name == self.name,
+            None => name == self.name || display_name(member) == self.name,
+        }
+    }
+
+    /// A case-insensitive regex from the pattern, or a plain substring
+    /// search when it is not a valid regex.
+    fn regex(&self) -> Regex {
+        RegexBuilder::new(&self.pattern)
+            .case_insensitive(true)
+            .build()
+            .unwrap_or_else(|_| {
+                RegexBuilder::new(&regex::escape(&self.pattern))
+                    .case_insensitive(true)
+                    .build()
+                    .unwrap()
+            })
+    }
+}
+
+/// The member's `name#id`, which picks them out even when names are shared.
+fn with_id(member: &Member) -> String {
+    format!(
+        "{}#{}",
+        member.name.as_deref().unwrap_or("Anonymous"),
+        member.id
+    )
+}
+
+/// Whether `regex` is found in the member's name or `name#id`.
+fn finds(regex: &Regex, member: &Member) -> bool {
+    regex.is_match(&display_name(member)) || regex.is_match(&with_id(member))
+}
+
+/// Drops the members left out by the `lists` for `aoc`'s leaderboard, so
+/// they neither show nor count towards anyone's points.
+pub fn restrict(aoc: &mut Aoc, lists: &HashMap<String, MemberLists>) {
+    let Some(lists) = lists.get(&aoc.owner_id.to_string()) else {
+        return;
+    };
+    let specs = |names: &[String]| -> Vec<MemberSpec> {
+        names.iter().map(|name| name.parse().unwrap()).collect()
+    };
+    let (include, exclude) = (specs(&lists.include), specs(&lists.exclude));
+    let before = aoc.members.len();
+    aoc.members.retain(|_, member| {
+        (include.is_empty() || include.iter().any(|spec| spec.matches(member)))
+            && !exclude.iter().any(|spec| spec.matches(member))
+    });
+    if aoc.members.len() != before {
+        replay::recount(aoc);
+    }
+}
+
+/// Resolves `specs` to the ids of the members they select. A spec that
+/// names no member is a pattern, like `^al` or `ice`, on every member's name
+/// and `name#id`.
+///
+/// # Errors
+///
+/// With [`Ambiguous::Error`], when a bare name or a pattern matches several
+/// members.
+pub fn resolve(
+    specs: &[MemberSpec],
+    members: &HashMap<String, Member>,
+    ambiguous: Ambiguous,
+    warnings: &mut Warnings,
+) -> Result<HashSet<i32>> {
+    let mut selected = HashSet::new();
+    for spec in specs {
+        let mut matching: Vec<&Member> = members.values().filter(|m| spec.matches(m)).collect();
+        let pattern = matching.is_empty();
+        if pattern {
+            let regex = spec.regex();
+            matching = members.values().filter(|m| finds(&regex, m)).collect();
+            if matching.is_empty() {
+                warnings.push(
+                    Category::Members,
+                    format!("'{}' matches no member", spec.pattern),
+                );
+            }
+        }
+        matching.sort_by_key(|m| m.id);
+        if matching.len() > 1 {
+            let candidates = matching
+                .iter()
+                .map(|m| with_id(m))
+                .collect::<Vec<_>>()
+                .join(", ");
+            match ambiguous {
+                Ambiguous::All if pattern => {}
+                Ambiguous::All => warnings.push(
+                    Category::Members,
+                    format!("'{}' matches {candidates}", spec.pattern),
+                ),
+                Ambiguous::Error => {
+                    return Err(Error::Usage {
+                        message: format!(
+                            "'{}' is ambiguous, use one of {candidates}",
+                            spec.pattern
+                        ),
+                    })
+                }
+            }
+        }
+        selected.extend(matching.iter().map(|m| m.id));
+    }
+    Ok(selected)
+}
+
+/// One test an event must pass to be shown in the timeline.
+#[derive(Clone, Copy, Debug)]
+pub enum EventFilter<'a> {
+    /// Earned on this local date.
+    On(NaiveDate),
+    /// Earned by one of these member ids.
+    Members(&'a HashSet<i32>),
+    /// A star of one of these puzzle days.
+    Days(&'a [u32]),
+    /// Earned at or after this instant.
+    Since(DateTime<Utc>),
+    /// Earned at or before this instant.
+    Until(DateTime<Utc>),
+}
+
+impl EventFilter<'_> {
+    pub fn keeps(&self, event: &Report) -> bool {
+        match self {
+            EventFilter::On(date) => event.timestamp.date_naive() == *date,
+            EventFilter::Members(ids) => ids.contains(&event.member_id),
+            EventFilter::Days(days) => days.contains(&event.day()),
+            EventFilter::Since(at) => event.timestamp >= *at,
+            EventFilter::Until(at) => event.timestamp <= *at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{board, member};
+
+    #[test]
+    fn name_and_id_picks_one_of_two_alexes() {
+        let members = board([
+            member(123, "Alex", &[]),
+            member(456, "Alex", &[]),
+            member(789, "Bob", &[]),
+        ])
+        .members;
+        let spec = "Alex#123".parse().unwrap();
+        assert_eq!(
+            resolve(
+                &[spec],
+                &members,
+                Ambiguous::Error,
+                &mut Warnings::default()
+            )
+            .unwrap(),
+            HashSet::from([123])
+        );
+
+        let bare: MemberSpec = "Alex".parse().unwrap();
+        assert_eq!(
+            resolve(
+                std::slice::from_ref(&bare),
+                &members,
+                Ambiguous::All,
+                &mut Warnings::default()
+            )
+            .unwrap(),
+            HashSet::from([123, 456])
+        );
+        assert!(resolve(
+            &[bare],
+            &members,
+            Ambiguous::Error,
+            &mut Warnings::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn unknown_name_is_a_pattern() {
+        let members = board([
+            member(123, "Alice", &[]),
+            member(456, "alfred", &[]),
+            member(789, "Bob", &[]),
+        ])
+        .members;
+        let resolve = |spec: &str| {
+            resolve(
+                &[spec.parse().unwrap()],
+                &members,
+                Ambiguous::All,
+                &mut Warnings::default(),
+            )
+            .unwrap()
+        };
+        assert_eq!(resolve("^al"), HashSet::from([123, 456]));
+        assert_eq!(resolve("#789$"), HashSet::from([789]));
+        assert_eq!(resolve("b("), HashSet::new());
+        assert_eq!(resolve("alice"), HashSet::from([123]));
+    }
+
+    #[test]
+    fn pattern_matching_several_is_ambiguous() {
+        let members = board([member(123, "Alice", &[]), member(456, "alfred", &[])]).members;
+        let resolve = |spec: &str| {
+            resolve(
+                &[spec.parse().unwrap()],
+                &members,
+                Ambiguous::Error,
+                &mut Warnings::default(),
+            )
+        };
+        let Err(Error::Usage { message }) = resolve("^al") else {
+            panic!("'^al' picked one of two members");
+        };
+        assert_eq!(
+            message,
+            "'^al' is ambiguous, use one of Alice#123, alfred#456"
+        );
+        assert_eq!(resolve("^ali").unwrap(), HashSet::from([123]));
+    }
+}
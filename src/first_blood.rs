@@ -0,0 +1,55 @@
+//! Who was first on the board to get each star.
+use crate::layout;
+use crate::{count_elapsed, timeline, Aoc, ElapsedFrom, Report, StarId};
+use itertools::Itertools;
+
+pub struct FirstBlood {
+    pub star: StarId,
+    /// Everyone sharing the shortest time since the unlock for the star,
+    /// with their elapsed times counted from the unlock.
+    pub winners: Vec<Report>,
+}
+
+/// Ranks both parts by the time since the puzzle unlock, and day 25's free
+/// star by when it was taken.
+pub fn first_blood(aoc: &Aoc) -> Vec<FirstBlood> {
+    let mut events = timeline(aoc);
+    count_elapsed(&mut events, aoc.year(), ElapsedFrom::Unlock);
+    events
+        .into_iter()
+        .into_group_map_by(|event| event.star)
+        .into_iter()
+        .map(|(star, events)| {
+            let key = |e: &Report| (e.elapsed, e.timestamp);
+            let first = events.iter().map(key).min().unwrap();
+            FirstBlood {
+                star,
+                winners: events.into_iter().filter(|e| key(e) == first).collect(),
+            }
+        })
+        .sorted_by(|a, b| a.star.cmp(&b.star))
+        .collect()
+}
+
+pub fn print(bloods: &[FirstBlood]) {
+    println!("\nFirst blood:");
+    for blood in bloods {
+        let winners = blood
+            .winners
+            .iter()
+            .map(|w| format!("{} ({})", w.member, w.elapsed_string()))
+            .join(", ");
+        println!("  {} {winners}", blood.star);
+    }
+    println!("\nFirst bloods per member:");
+    let tally = bloods
+        .iter()
+        .flat_map(|b| b.winners.iter().map(|w| &*w.member))
+        .counts();
+    for (member, count) in tally
+        .into_iter()
+        .sorted_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)))
+    {
+        println!("  {} {count}", layout::name(member));
+    }
+}
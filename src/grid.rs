@@ -0,0 +1,61 @@
+//! Who has which star, like the personal stats page on adventofcode.com.
+use crate::layout;
+use crate::{display_names, Aoc};
+use serde::Serialize;
+
+/// A member's stars per day, from day 1 to 25.
+#[derive(Debug, Serialize)]
+pub struct GridRow {
+    pub member: String,
+    pub stars: Vec<u8>,
+}
+
+/// One row per member, most stars first.
+pub fn grid(aoc: &Aoc) -> Vec<GridRow> {
+    let names = display_names(&aoc.members);
+    let mut rows: Vec<GridRow> = aoc
+        .members
+        .values()
+        .map(|member| GridRow {
+            member: names[&member.id].clone(),
+            stars: (1..=25)
+                .map(|day| {
+                    member
+                        .completion_day_level
+                        .get(&day)
+                        .map_or(0, |stars| u8::try_from(stars.len()).unwrap())
+                })
+                .collect(),
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        let total = |row: &GridRow| row.stars.iter().map(|s| u32::from(*s)).sum::<u32>();
+        total(b)
+            .cmp(&total(a))
+            .then_with(|| a.member.cmp(&b.member))
+    });
+    rows
+}
+
+pub fn print(grid: &[GridRow]) {
+    println!();
+    print!("  {:width$}", "", width = layout::name_width());
+    for day in 1..=25 {
+        print!(" {day:>2}");
+    }
+    println!();
+    for row in grid {
+        print!("  {}", layout::name(&row.member));
+        for stars in &row.stars {
+            print!(
+                " {}",
+                match stars {
+                    0 => " ·",
+                    1 => " *",
+                    _ => "**",
+                }
+            );
+        }
+        println!();
+    }
+}
@@ -0,0 +1,99 @@
+//! Every fetched leaderboard kept as a timestamped snapshot, one directory
+//! per board, so rank evolution and diffs can be computed later.
+use crate::config::{Config, History};
+use crate::error::{Error, Result};
+use crate::fetch::read_leaderboard;
+use crate::{Aoc, Board};
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// File names sort in time order and avoid `:` for Windows.
+const FORMAT: &str = "%Y-%m-%dT%H-%M-%SZ";
+
+fn directory(history: &History, board: Board) -> PathBuf {
+    history
+        .dir
+        .join(format!("{}-{}", board.year, board.leaderboard))
+}
+
+fn write_error(path: &Path) -> impl Fn(std::io::Error) -> Error + '_ {
+    move |source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// Snapshots of `board`, oldest first.
+///
+/// # Errors
+///
+/// When the history directory exists but cannot be read.
+pub fn snapshots(history: &History, board: Board) -> Result<Vec<(DateTime<Utc>, PathBuf)>> {
+    let dir = directory(history, board);
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(&dir).map_err(|source| Error::CacheRead {
+        path: dir.clone(),
+        source,
+    })?;
+    let mut snapshots: Vec<_> = entries
+        .filter_map(std::result::Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            let stem = path.file_stem()?.to_str()?;
+            let time = NaiveDateTime::parse_from_str(stem, FORMAT).ok()?;
+            Some((Utc.from_utc_datetime(&time), path))
+        })
+        .collect();
+    snapshots.sort();
+    Ok(snapshots)
+}
+
+/// Reads one snapshot.
+///
+/// # Errors
+///
+/// When the file cannot be read or is not leaderboard JSON.
+pub fn load(path: &Path) -> Result<Aoc> {
+    read_leaderboard(path)
+}
+
+/// Removes snapshots beyond the configured count or age.
+///
+/// # Errors
+///
+/// When the history cannot be listed or a snapshot cannot be removed.
+pub fn prune(history: &History, board: Board, now: DateTime<Utc>) -> Result<()> {
+    let snapshots = snapshots(history, board)?;
+    let over = history
+        .keep
+        .map_or(0, |keep| snapshots.len().saturating_sub(keep));
+    let cutoff = history.max_days.map(|days| now - Duration::days(days));
+    for (i, (time, path)) in snapshots.iter().enumerate() {
+        if i < over || cutoff.is_some_and(|cutoff| *time < cutoff) {
+            std::fs::remove_file(path).map_err(write_error(path))?;
+        }
+    }
+    Ok(())
+}
+
+/// Stores a freshly fetched `aoc` when the config keeps history, then
+/// prunes.
+///
+/// # Errors
+///
+/// When the snapshot cannot be written or pruning fails.
+pub fn record(config: &Config, board: Board, aoc: &Aoc) -> Result<()> {
+    let Some(history) = &config.history else {
+        return Ok(());
+    };
+    let now = Utc::now();
+    let dir = directory(history, board);
+    std::fs::create_dir_all(&dir).map_err(write_error(&dir))?;
+    let path = dir.join(format!("{}.json", now.format(FORMAT)));
+    std::fs::write(&path, serde_json::to_string(aoc).unwrap()).map_err(write_error(&path))?;
+    debug!("archived in {}", path.display());
+    prune(history, board, now)
+}
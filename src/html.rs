@@ -0,0 +1,202 @@
+//! A self-contained HTML report: inline style, collapsible days and tables
+//! that sort when a header is clicked.
+use crate::locale;
+use crate::render::{self, local_difference, LeaderboardReport, ScoredEvent};
+use crate::{duration_string, streaks};
+use std::fmt::Write as _;
+
+const STYLE: &str = "\
+body{font-family:system-ui,sans-serif;margin:2em auto;max-width:60em;color:#222}
+h1,h2{font-weight:600}
+table{border-collapse:collapse;margin:.5em 0 1em}
+th,td{padding:.2em .8em;text-align:left}
+th{cursor:pointer;border-bottom:2px solid #999;user-select:none}
+tr:nth-child(even) td{background:#f3f3f3}
+td.n{text-align:right;font-variant-numeric:tabular-nums}
+summary{cursor:pointer;font-weight:600;margin:.4em 0}
+.note{font-style:italic;color:#555}";
+
+const SCRIPT: &str = "\
+document.querySelectorAll('th').forEach(th=>th.addEventListener('click',()=>{
+const table=th.closest('table'),body=table.tBodies[0],i=th.cellIndex;
+const asc=th.dataset.dir!=='asc';th.dataset.dir=asc?'asc':'desc';
+const key=r=>{const c=r.cells[i];return c.dataset.v??c.textContent};
+[...body.rows].sort((a,b)=>{const x=key(a),y=key(b);
+const d=isNaN(x)||isNaN(y)?x.localeCompare(y):x-y;return asc?d:-d})
+.forEach(r=>body.appendChild(r))}));";
+
+/// Escapes text for use in element content and attribute values.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Wraps `body` in a complete page with the report style and sorting script.
+pub fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n{body}\
+         <script>\n{SCRIPT}\n</script>\n</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+/// The timeline as one collapsible section per day followed by the
+/// leaderboard, without the surrounding page.
+pub fn section(report: &LeaderboardReport) -> String {
+    let mut html = format!(
+        "<h1>{} leaderboard {}</h1>\n",
+        report.year, report.leaderboard
+    );
+    // Closes a day's table with the part 2 podium underneath.
+    let close = |html: &mut String, day: u32| {
+        html.push_str("</tbody></table>");
+        let places: Vec<String> = render::podium(report, day)
+            .iter()
+            .map(|(place, member, time)| {
+                format!("{place}. {} {}", escape(member), duration_string(*time))
+            })
+            .collect();
+        if !places.is_empty() {
+            write!(
+                html,
+                "<p class=\"note\">Podium {day:02}-2: {}</p>",
+                places.join(", ")
+            )
+            .unwrap();
+        }
+        html.push_str("</details>\n");
+    };
+    let mut day = String::new();
+    let mut day_number = 0;
+    for ScoredEvent { event, points, .. } in &report.events {
+        let event_day = locale::format(&event.timestamp, "%B %-d");
+        if event_day != day {
+            if !day.is_empty() {
+                close(&mut html, day_number);
+            }
+            day_number = 0;
+            write!(
+                html,
+                "<details open><summary>{event_day}</summary>\n<table><thead><tr>\
+                 <th>Time</th><th>Member</th><th>Star</th><th>Points</th><th>Elapsed</th>\
+                 <th>Since part 1</th></tr></thead><tbody>\n"
+            )
+            .unwrap();
+            day = event_day;
+        }
+        day_number = day_number.max(event.day());
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td class=\"n\">{points}</td>\
+             <td class=\"n\" data-v=\"{}\">{}</td><td class=\"n\" data-v=\"{}\">{}</td></tr>",
+            event.timestamp.time(),
+            escape(&event.member),
+            event.star,
+            event.elapsed.num_seconds(),
+            event.elapsed_string(),
+            event.delta.map_or(-1, |delta| delta.num_seconds()),
+            event.delta.map(duration_string).unwrap_or_default()
+        )
+        .unwrap();
+    }
+    if !day.is_empty() {
+        close(&mut html, day_number);
+    }
+    html.push_str(&standings(report));
+    html
+}
+
+/// The leaderboard table and the streaks under it.
+fn standings(report: &LeaderboardReport) -> String {
+    let mut html = String::from("<h2>Leaderboard</h2>\n");
+    if let (Some(min), Some(time_standings)) = (report.min_members, &report.time_standings) {
+        writeln!(
+            html,
+            "<p class=\"note\">Fewer than {min} members, ranking by stars and total solve time.</p>"
+        )
+        .unwrap();
+        html.push_str(
+            "<table><thead><tr><th>#</th><th>Member</th><th>Stars</th><th>Solve time</th>\
+             </tr></thead><tbody>\n",
+        );
+        for (rank, score) in time_standings.iter().enumerate() {
+            writeln!(
+                html,
+                "<tr><td class=\"n\">{}</td><td>{}</td><td class=\"n\">{}</td>\
+                 <td class=\"n\" data-v=\"{}\">{}</td></tr>",
+                rank + 1,
+                escape(&score.member),
+                score.stars,
+                score.time.num_seconds(),
+                duration_string(score.time)
+            )
+            .unwrap();
+        }
+    } else {
+        let local = report.standings.iter().any(|s| s.local_score.is_some());
+        writeln!(
+            html,
+            "<table><thead><tr><th>#</th><th>Member</th><th>{}</th>{}</tr></thead><tbody>",
+            report.scoring.label(),
+            if local {
+                "<th>AoC</th><th>Difference</th>"
+            } else {
+                ""
+            }
+        )
+        .unwrap();
+        for score in &report.standings {
+            write!(
+                html,
+                "<tr><td class=\"n\">{}</td><td>{}</td><td class=\"n\" data-v=\"{}\">{}{}</td>",
+                score.rank,
+                escape(&score.member),
+                score.points,
+                score.points,
+                score
+                    .percent
+                    .map(|percent| format!(" ({percent:.1}%)"))
+                    .unwrap_or_default()
+            )
+            .unwrap();
+            if let (Some(local), Some(difference)) = (score.local_score, local_difference(score)) {
+                write!(
+                    html,
+                    "<td class=\"n\">{local}</td><td class=\"n\">{difference}</td>"
+                )
+                .unwrap();
+            }
+            html.push_str("</tr>\n");
+        }
+    }
+    html.push_str("</tbody></table>\n");
+    if let Some(more) = report.more.filter(|more| *more > 0) {
+        writeln!(html, "<p class=\"note\">… and {more} more</p>").unwrap();
+    }
+    if !report.streaks.is_empty() {
+        writeln!(
+            html,
+            "<p class=\"note\">Streaks (both stars on release day): {}</p>",
+            streaks::summary(&report.streaks, escape)
+        )
+        .unwrap();
+    }
+    html
+}
+
+/// Every leaderboard on a single page.
+pub fn report(reports: &[LeaderboardReport]) -> String {
+    let body: String = reports.iter().map(section).collect();
+    page("Advent of Code timeline", &body)
+}
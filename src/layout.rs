@@ -0,0 +1,73 @@
+//! Column widths that fit the terminal the text format is printed to.
+use crate::column;
+use std::sync::OnceLock;
+
+/// The width of the layout when it is not printed to a terminal, and the
+/// most it grows to on one.
+const FULL: usize = 70;
+/// The name column at [`FULL`] width, and the narrowest it gets.
+const NAME: usize = 25;
+const MIN_NAME: usize = 12;
+/// The timeline line around the name: `* 05:15:14 ` before it, the tab and
+/// `01-2 [12] (1:02:03) +12:34` after.
+const AROUND_NAME: usize = FULL - NAME;
+/// Narrower than this, timeline details go on a line of their own.
+const STACK_BELOW: usize = AROUND_NAME + MIN_NAME - 1;
+
+static WIDTH: OnceLock<Option<usize>> = OnceLock::new();
+
+/// Measures the terminal, or takes `COLUMNS` when set, for the rest of the
+/// run. Call it before stdout goes to a pager.
+pub fn detect() {
+    WIDTH.get_or_init(|| {
+        let columns = std::env::var("COLUMNS").ok().and_then(|c| c.parse().ok());
+        columns.or_else(terminal_width).filter(|width| *width > 0)
+    });
+}
+
+#[cfg(unix)]
+fn terminal_width() -> Option<usize> {
+    use std::io::IsTerminal;
+
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ only fills in `size`.
+    let status = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (status == 0).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(not(unix))]
+fn terminal_width() -> Option<usize> {
+    None
+}
+
+fn width() -> usize {
+    WIDTH.get().copied().flatten().unwrap_or(FULL).min(FULL)
+}
+
+/// The `####` line between leaderboards.
+pub fn rule() -> String {
+    "#".repeat(width())
+}
+
+/// How wide member names are padded and cut.
+pub fn name_width() -> usize {
+    width().saturating_sub(AROUND_NAME).clamp(MIN_NAME, NAME)
+}
+
+/// `name` padded or cut to [`name_width`].
+pub fn name(name: &str) -> String {
+    column(name, name_width())
+}
+
+/// Whether the terminal is too narrow for a star on one line.
+pub fn stacked() -> bool {
+    width() < STACK_BELOW
+}
@@ -0,0 +1,648 @@
+//! Timelines and standings for Advent of Code private leaderboards.
+#![warn(clippy::pedantic)]
+#![allow(clippy::must_use_candidate)]
+#![allow(clippy::missing_panics_doc)]
+#![allow(clippy::implicit_hasher)]
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+pub mod achievements;
+pub mod anonymize;
+pub mod atomic;
+pub mod baseline;
+pub mod cache;
+pub mod canonical;
+#[cfg(feature = "chart")]
+pub mod chart;
+pub mod color;
+pub mod combined;
+pub mod compare;
+pub mod config;
+pub mod days;
+#[cfg(feature = "sqlite")]
+pub mod db;
+pub mod diff;
+pub mod digest;
+pub mod elo;
+pub mod error;
+pub mod export;
+pub mod fetch;
+pub mod filter;
+pub mod first_blood;
+pub mod grid;
+pub mod history;
+pub mod html;
+pub mod layout;
+pub mod locale;
+pub mod logging;
+pub mod medals;
+pub mod merge;
+pub mod metrics;
+pub mod notify;
+pub mod output;
+pub mod pace;
+pub mod pager;
+pub mod predict;
+pub mod progress;
+pub mod ranks;
+pub mod render;
+pub mod replay;
+pub mod runtime;
+pub mod sample;
+pub mod scoring;
+pub mod serve;
+pub mod site;
+pub mod stats;
+pub mod streaks;
+pub mod svg;
+pub mod template;
+pub mod throttle;
+pub mod tui;
+pub mod warnings;
+pub mod watch;
+
+pub const YEAR: i32 = 2024;
+pub const LEADERBOARDS: [i32; 2] = [649_161, 1_027_450];
+pub const CACHEFILE: &str = ".aoc.json";
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Star {
+    pub get_star_ts: i64,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Member {
+    pub global_score: i32,
+    pub name: Option<String>,
+    pub stars: i32,
+    pub id: i32,
+    pub last_star_ts: i64,
+    pub local_score: i32,
+    pub completion_day_level: HashMap<u32, HashMap<u32, Star>>,
+}
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct Aoc {
+    pub event: String,
+    pub owner_id: i32,
+    pub members: HashMap<String, Member>,
+}
+
+impl Aoc {
+    pub fn year(&self) -> i32 {
+        self.event.parse().unwrap_or(YEAR)
+    }
+}
+
+/// One private leaderboard in one year's event.
+#[derive(Clone, Copy, Debug)]
+pub struct Board {
+    pub year: i32,
+    pub leaderboard: i32,
+}
+
+impl Board {
+    pub fn cache_key(self) -> String {
+        format!("{}/{}", self.year, self.leaderboard)
+    }
+}
+
+/// Which star of the event, shown like `07-2` for day 7's second star.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StarId {
+    pub day: u32,
+    pub part: u32,
+}
+
+impl fmt::Display for StarId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}-{}", self.day, self.part)
+    }
+}
+
+impl Serialize for StarId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// One star earned by one member, serialized with `day` and `part` as
+/// well as the `star`.
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub timestamp: DateTime<Local>,
+    pub elapsed: Duration,
+    /// Time from the member's part 1 to this part 2 star.
+    pub delta: Option<Duration>,
+    /// Shared by all of the member's stars.
+    pub member: Arc<str>,
+    pub member_id: i32,
+    pub star: StarId,
+}
+
+impl Serialize for Report {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut report = serializer.serialize_struct("Report", 8)?;
+        report.serialize_field("timestamp", &self.timestamp)?;
+        report.serialize_field("elapsed_seconds", &self.elapsed.num_seconds())?;
+        report.serialize_field("delta_seconds", &self.delta.map(|d| d.num_seconds()))?;
+        report.serialize_field("member", &self.member)?;
+        report.serialize_field("member_id", &self.member_id)?;
+        report.serialize_field("day", &self.star.day)?;
+        report.serialize_field("part", &self.star.part)?;
+        report.serialize_field("star", &self.star)?;
+        report.end()
+    }
+}
+
+impl Report {
+    /// The puzzle day of the `star`.
+    pub fn day(&self) -> u32 {
+        self.star.day
+    }
+
+    /// The part of the `star`, 1 or 2.
+    pub fn part(&self) -> u32 {
+        self.star.part
+    }
+
+    /// The solve time like `12:34`, or `free` for day 25's second star.
+    pub fn elapsed_string(&self) -> String {
+        if automatic(self.day(), self.part()) {
+            "free".to_string()
+        } else {
+            duration_string(self.elapsed)
+        }
+    }
+}
+
+/// Every star on a board in the order they were earned.
+pub type Timeline = Vec<Report>;
+
+/// A member's stars and the total time it took to get them.
+#[derive(Clone, Debug, Serialize)]
+pub struct TimeScore {
+    pub member: String,
+    pub stars: usize,
+    #[serde(rename = "solve_seconds", serialize_with = "seconds")]
+    pub time: Duration,
+}
+
+/// Durations are serialized as whole seconds.
+pub(crate) fn seconds<S: Serializer>(d: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_i64(d.num_seconds())
+}
+
+/// Like [`seconds`], for durations that may be missing.
+#[allow(clippy::ref_option)] // serde hands fields over by reference
+pub(crate) fn optional_seconds<S: Serializer>(
+    d: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match d {
+        Some(d) => serializer.serialize_some(&d.num_seconds()),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// A member's total points.
+#[derive(Clone, Debug, Serialize)]
+pub struct Score {
+    /// Place on the board, shared with members still tied after the
+    /// tie-breakers.
+    pub rank: usize,
+    pub member: String,
+    pub points: usize,
+    /// Percent of the most points possible so far, with `--normalize`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub percent: Option<f64>,
+    /// The score Advent of Code reports, with `--local-score`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_score: Option<i32>,
+}
+
+/// Puzzles unlock at midnight US/Eastern, which is always UTC-5 in December.
+pub fn unlock(year: i32, day: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, 12, day, 5, 0, 0)
+        .single()
+        .unwrap()
+}
+
+/// What a star's elapsed time is counted from, picked with `--elapsed-from`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ElapsedFrom {
+    /// The member's previous star of the day, so part 2 is the time after
+    /// part 1.
+    #[default]
+    Previous,
+    /// The puzzle unlock, for both parts.
+    Unlock,
+}
+
+/// Counts the elapsed time of `events` of `year` from `from`, where
+/// [`timeline`] counts from the previous star.
+pub fn count_elapsed<'a>(
+    events: impl IntoIterator<Item = &'a mut Report>,
+    year: i32,
+    from: ElapsedFrom,
+) {
+    if from == ElapsedFrom::Previous {
+        return;
+    }
+    for event in events {
+        if !automatic(event.day(), event.part()) {
+            event.elapsed = event.timestamp.with_timezone(&Utc) - unlock(year, event.day());
+        }
+    }
+}
+
+/// Sets the negative elapsed times and deltas of `events`, which come from
+/// skewed clocks or odd API data rather than real solves, to zero, and
+/// describes each one.
+pub fn clamp_elapsed<'a>(events: impl IntoIterator<Item = &'a mut Report>) -> Vec<String> {
+    let mut clamped = Vec::new();
+    for event in events {
+        for (what, time) in [
+            ("elapsed time", Some(&mut event.elapsed)),
+            ("time since part 1", event.delta.as_mut()),
+        ] {
+            if let Some(time) = time.filter(|time| **time < Duration::zero()) {
+                clamped.push(format!(
+                    "{} {} at {} has a {what} of {}, shown as 00:00",
+                    event.member,
+                    event.star,
+                    locale::format(&event.timestamp, "%b %e %H:%M:%S"),
+                    duration_string(*time)
+                ));
+                *time = Duration::zero();
+            }
+        }
+    }
+    clamped
+}
+
+/// FNV-1a, a hash that unlike the standard library's is the same in every
+/// Rust version, for picking things that stay the same between runs.
+pub(crate) fn stable_hash(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Day 25's second star comes with the other 49, so there is nothing to
+/// solve and its time says nothing.
+pub fn automatic(day: u32, part: u32) -> bool {
+    day == 25 && part == 2
+}
+
+pub fn display_name(member: &Member) -> String {
+    if let Some(name) = member.name.clone() {
+        name
+    } else {
+        format!("Anonymous#{}", member.id)
+    }
+}
+
+/// Display names keyed by member id, with `#id` appended to names that more
+/// than one member on the board shares.
+pub fn display_names(members: &HashMap<String, Member>) -> HashMap<i32, String> {
+    let counts = members.values().map(display_name).counts();
+    members
+        .values()
+        .map(|member| {
+            let name = display_name(member);
+            if counts[&name] > 1 {
+                (member.id, format!("{name}#{}", member.id))
+            } else {
+                (member.id, name)
+            }
+        })
+        .collect()
+}
+
+pub fn duration_string(d: Duration) -> String {
+    if d < Duration::zero() {
+        format!("-{}", duration_string(-d))
+    } else if d.num_days() > 0 {
+        format!(
+            "{}d {}:{:02}:{:02}",
+            d.num_days(),
+            d.num_hours() % 24,
+            d.num_minutes() % 60,
+            d.num_seconds() % 60
+        )
+    } else if d.num_hours() > 0 {
+        format!(
+            "{}:{:02}:{:02}",
+            d.num_hours(),
+            d.num_minutes() % 60,
+            d.num_seconds() % 60
+        )
+    } else {
+        format!("{:02}:{:02}", d.num_minutes() % 60, d.num_seconds() % 60)
+    }
+}
+
+/// `text` padded to `width` terminal columns, or cut short with an ellipsis
+/// when wider, counting emoji and CJK characters as two columns.
+pub fn column(text: &str, width: usize) -> String {
+    let mut column = String::new();
+    let mut used = text.width();
+    if used <= width {
+        column.push_str(text);
+    } else {
+        used = 0;
+        for c in text.chars() {
+            let c_width = c.width().unwrap_or(0);
+            if used + c_width >= width {
+                break;
+            }
+            column.push(c);
+            used += c_width;
+        }
+        column.push('…');
+        used += 1;
+    }
+    column.extend(std::iter::repeat_n(' ', width.saturating_sub(used)));
+    column
+}
+
+pub fn timeline(aoc: &Aoc) -> Timeline {
+    let year = aoc.year();
+    let names: HashMap<i32, Arc<str>> = display_names(&aoc.members)
+        .into_iter()
+        .map(|(id, name)| (id, name.into()))
+        .collect();
+    let total: i32 = aoc.members.values().map(|member| member.stars).sum();
+    let mut timeline = Vec::<Report>::with_capacity(usize::try_from(total).unwrap_or(0));
+    for member in aoc.members.values() {
+        for dayno in member.completion_day_level.keys().sorted() {
+            let day = &member.completion_day_level[dayno];
+            let mut start = unlock(year, *dayno).with_timezone(&Local);
+            let part1 = day
+                .get(&1)
+                .map(|star| Local.timestamp_opt(star.get_star_ts, 0).single().unwrap());
+            for star in 1..=2 {
+                if day.contains_key(&star) {
+                    let solvetime = Local
+                        .timestamp_opt(day[&star].get_star_ts, 0)
+                        .single()
+                        .unwrap();
+                    let free = automatic(*dayno, star);
+                    timeline.push(Report {
+                        timestamp: solvetime,
+                        elapsed: if free {
+                            Duration::zero()
+                        } else {
+                            solvetime - start
+                        },
+                        delta: part1
+                            .filter(|_| star == 2 && !free)
+                            .map(|part1| solvetime - part1),
+                        member: names[&member.id].clone(),
+                        member_id: member.id,
+                        star: StarId {
+                            day: *dayno,
+                            part: star,
+                        },
+                    });
+                    start = solvetime;
+                }
+            }
+        }
+    }
+    timeline.sort_by_key(|a| a.timestamp);
+    timeline
+}
+
+/// Pairs every event with the points it earned: the first to get a star
+/// scores one point per member, the next one point less and so on. Day 25's
+/// [`automatic`] second star scores nothing.
+pub fn scored(aoc: &Aoc) -> Vec<(Report, usize)> {
+    let max_score = aoc.members.len();
+    let mut score: HashMap<StarId, usize> = HashMap::new();
+    timeline(aoc)
+        .into_iter()
+        .map(|event| {
+            if automatic(event.day(), event.part()) {
+                return (event, 0);
+            }
+            let star_score = *score
+                .entry(event.star)
+                .and_modify(|e| *e -= 1)
+                .or_insert(max_score);
+            (event, star_score)
+        })
+        .collect()
+}
+
+pub fn standings(events: &[(Report, usize)]) -> Vec<Score> {
+    let mut total_score: HashMap<&str, usize> = HashMap::new();
+    for (event, star_score) in events {
+        *total_score.entry(&event.member).or_default() += star_score;
+    }
+    let mut standings: Vec<Score> = total_score
+        .into_iter()
+        .map(|(member, points)| Score {
+            rank: 0,
+            member: member.to_string(),
+            points,
+            percent: None,
+            local_score: None,
+        })
+        .sorted_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then_with(|| a.member.cmp(&b.member))
+        })
+        .collect();
+    rank(&mut standings, |a, b| a.points == b.points);
+    standings
+}
+
+/// Numbers `standings`, which are best first, giving the same place to
+/// neighbours that are `tied`.
+pub fn rank(standings: &mut [Score], tied: impl Fn(&Score, &Score) -> bool) {
+    for i in 0..standings.len() {
+        standings[i].rank = if i > 0 && tied(&standings[i - 1], &standings[i]) {
+            standings[i - 1].rank
+        } else {
+            i + 1
+        };
+    }
+}
+
+/// Stars and total solve time per member, most stars and least time first.
+pub fn time_standings(events: &[(Report, usize)]) -> Vec<TimeScore> {
+    let mut totals: HashMap<&str, (usize, Duration)> = HashMap::new();
+    for (event, _) in events {
+        let total = totals.entry(&event.member).or_insert((0, Duration::zero()));
+        total.0 += 1;
+        total.1 = total.1 + event.elapsed;
+    }
+    totals
+        .into_iter()
+        .map(|(member, (stars, time))| TimeScore {
+            member: member.to_string(),
+            stars,
+            time,
+        })
+        .sorted_by(|a, b| b.stars.cmp(&a.stars).then(a.time.cmp(&b.time)))
+        .collect()
+}
+
+/// Members and boards for the tests of every module.
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::{Aoc, Member, Star};
+    use std::collections::HashMap;
+
+    /// A member with a star at `ts` for every `(day, part, ts)`, and the star
+    /// count and last star to match.
+    pub fn member(id: i32, name: &str, stars: &[(u32, u32, i64)]) -> Member {
+        let mut completion_day_level: HashMap<u32, HashMap<u32, Star>> = HashMap::new();
+        for &(day, part, get_star_ts) in stars {
+            completion_day_level
+                .entry(day)
+                .or_default()
+                .insert(part, Star { get_star_ts });
+        }
+        Member {
+            global_score: 0,
+            name: Some(name.to_string()),
+            stars: i32::try_from(stars.len()).unwrap(),
+            id,
+            last_star_ts: stars.iter().map(|&(_, _, ts)| ts).max().unwrap_or(0),
+            local_score: 0,
+            completion_day_level,
+        }
+    }
+
+    /// A 2024 board owned by member 1 with `members`, keyed by id.
+    pub fn board(members: impl IntoIterator<Item = Member>) -> Aoc {
+        Aoc {
+            event: "2024".to_string(),
+            owner_id: 1,
+            members: members
+                .into_iter()
+                .map(|member| (member.id.to_string(), member))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{board, member};
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn unlock_is_midnight_in_new_york() {
+        let unlocked = unlock(2024, 1);
+        for (east, expected) in [
+            (0, "2024-12-01 05:00"),
+            (-5 * 3600, "2024-12-01 00:00"),
+            (-8 * 3600, "2024-11-30 21:00"),
+            (3600, "2024-12-01 06:00"),
+            (5 * 3600 + 1800, "2024-12-01 10:30"),
+            (9 * 3600, "2024-12-01 14:00"),
+        ] {
+            let zone = FixedOffset::east_opt(east).unwrap();
+            assert_eq!(
+                unlocked
+                    .with_timezone(&zone)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+                expected
+            );
+        }
+    }
+
+    /// A board with one member who got both stars of `day` after 10 and 25
+    /// minutes.
+    fn one_day(day: u32) -> Aoc {
+        let start = unlock(2024, day).timestamp();
+        board([Member {
+            local_score: 2,
+            ..member(
+                1,
+                "Alice",
+                &[(day, 1, start + 10 * 60), (day, 2, start + 25 * 60)],
+            )
+        }])
+    }
+
+    /// Run by `elapsed_is_the_same_in_every_time_zone` in a process of its
+    /// own with `TZ` set, as changing it here would affect other tests.
+    #[test]
+    #[ignore = "run with TZ set by elapsed_is_the_same_in_every_time_zone"]
+    fn elapsed_in_this_time_zone() {
+        let elapsed: Vec<i64> = timeline(&one_day(3))
+            .iter()
+            .map(|event| event.elapsed.num_minutes())
+            .collect();
+        assert_eq!(elapsed, [10, 15]);
+    }
+
+    #[test]
+    fn elapsed_is_the_same_in_every_time_zone() {
+        for zone in [
+            "UTC",
+            "America/New_York",
+            "America/Los_Angeles",
+            "Europe/Oslo",
+            "Asia/Kolkata",
+            "Australia/Adelaide",
+        ] {
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", "tests::elapsed_in_this_time_zone", "--ignored"])
+                .env("TZ", zone)
+                .stdout(std::process::Stdio::null())
+                .status()
+                .unwrap();
+            assert!(status.success(), "in {zone}");
+        }
+    }
+
+    #[test]
+    fn day_25_part_2_has_no_time() {
+        let timeline = timeline(&one_day(25));
+        assert_eq!(timeline[0].elapsed_string(), "10:00");
+        assert_eq!(timeline[1].elapsed, Duration::zero());
+        assert_eq!(timeline[1].delta, None);
+        assert_eq!(timeline[1].elapsed_string(), "free");
+    }
+
+    #[test]
+    fn stars_before_the_unlock_are_clamped() {
+        let mut aoc = one_day(4);
+        let member = aoc.members.get_mut("1").unwrap();
+        let day = member.completion_day_level.get_mut(&4).unwrap();
+        day.get_mut(&1).unwrap().get_star_ts = unlock(2024, 4).timestamp() - 90;
+        let mut timeline = timeline(&aoc);
+        assert_eq!(duration_string(timeline[0].elapsed), "-01:30");
+        let clamped = clamp_elapsed(&mut timeline);
+        assert_eq!(clamped.len(), 1);
+        assert!(clamped[0].starts_with("Alice 04-1 at "), "{}", clamped[0]);
+        assert_eq!(timeline[0].elapsed, Duration::zero());
+        assert_eq!(
+            timeline[1].elapsed,
+            Duration::minutes(26) + Duration::seconds(30)
+        );
+    }
+
+    #[test]
+    fn columns_count_wide_characters() {
+        assert_eq!(column("Alice", 8), "Alice   ");
+        assert_eq!(column("🎄 Elf", 8), "🎄 Elf  ");
+        assert_eq!(column("東京太郎", 8), "東京太郎");
+        assert_eq!(column("東京太郎さん", 8), "東京太… ");
+        assert_eq!(column("Bartholomew", 8), "Barthol…");
+    }
+}
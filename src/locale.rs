@@ -0,0 +1,119 @@
+//! Month and weekday names and date order in the language picked with
+//! `--locale`, from chrono's copy of the glibc locale data.
+use chrono::{DateTime, NaiveDate, TimeZone};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// A locale dates can be written in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Locale(chrono::Locale);
+
+impl FromStr for Locale {
+    type Err = String;
+
+    /// Takes a locale like `sv_SE.UTF-8` or `pt-BR`, or a language code like
+    /// `de`, which gets the country of the same name if it has one, like
+    /// `de_DE`, and otherwise the first country with that language.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let unknown = || format!("unknown locale '{s}'");
+        let name = s.split('.').next().unwrap_or_default().replace('-', "_");
+        let (language, country) = match name.split_once('_') {
+            Some((language, country)) => (language.to_lowercase(), Some(country)),
+            None => (name.to_lowercase(), None),
+        };
+        let parse = |country: &str| chrono::Locale::from_str(&format!("{language}_{country}"));
+        let locale = match (language.as_str(), country) {
+            ("c" | "posix", None) => chrono::Locale::POSIX,
+            ("en", None) => chrono::Locale::en_US,
+            (_, Some(country)) => parse(&country.to_uppercase()).map_err(|_| unknown())?,
+            (_, None) => {
+                let letters = || b'A'..=b'Z';
+                let countries = letters()
+                    .flat_map(|a| letters().map(move |b| String::from_utf8(vec![a, b])))
+                    .flatten();
+                std::iter::once(language.to_uppercase())
+                    .chain(countries)
+                    .find_map(|country| parse(&country).ok())
+                    .ok_or_else(unknown)?
+            }
+        };
+        Ok(Locale(locale))
+    }
+}
+
+impl Locale {
+    /// Whether the day goes before the month, which is so unless the
+    /// locale's numeric dates start with the month, like `12/07/2024`.
+    fn day_first(self) -> bool {
+        let date = NaiveDate::from_ymd_opt(2001, 2, 3).unwrap_or_default();
+        !date
+            .format_localized("%x", self.0)
+            .to_string()
+            .starts_with("02")
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Writes dates in `locale` for the rest of the run.
+pub fn set(locale: Locale) {
+    LOCALE.get_or_init(|| locale);
+}
+
+/// Formats `time` like [`DateTime::format_localized`] in `--locale`, with the
+/// day before the month where the locale writes it so. Patterns are written
+/// for English, like `%B %e` or `%b %-d, %Y`.
+pub fn format<Tz: TimeZone>(time: &DateTime<Tz>, pattern: &str) -> String
+where
+    Tz::Offset: Display,
+{
+    localized(time, pattern, LOCALE.get().copied().unwrap_or_default())
+}
+
+/// Formats `time` with `pattern` in `locale`.
+fn localized<Tz: TimeZone>(time: &DateTime<Tz>, pattern: &str, locale: Locale) -> String
+where
+    Tz::Offset: Display,
+{
+    if !locale.day_first() {
+        return time.format_localized(pattern, locale.0).to_string();
+    }
+    let mut pattern = pattern.to_string();
+    for month in ["%B", "%b"] {
+        for day in ["%e", "%-d"] {
+            // A padded day only lines up columns when it comes second.
+            let day_month = format!("%-d {month}");
+            pattern = pattern
+                .replace(&format!("{month} {day}, %Y"), &format!("{day_month} %Y"))
+                .replace(&format!("{month} {day}"), &day_month);
+        }
+    }
+    time.format_localized(&pattern, locale.0).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn write(locale: &str, pattern: &str) -> String {
+        let time = Utc.with_ymd_and_hms(2024, 12, 7, 6, 5, 4).unwrap();
+        localized(&time, pattern, locale.parse().unwrap())
+    }
+
+    #[test]
+    fn names_and_order_come_from_the_locale() {
+        assert_eq!(
+            write("de-DE.UTF-8", "%A, %B %e, %Y %H:%M"),
+            "Samstag, 7 Dezember 2024 06:05"
+        );
+        assert_eq!(write("en", "%a %b %e, %Y"), "Sat Dec  7, 2024");
+        assert_eq!(write("fr", "%b %-d"), "7 déc.");
+        assert_eq!("da".parse(), Ok(Locale(chrono::Locale::da_DK)));
+        assert_eq!("pt_br".parse(), Ok(Locale(chrono::Locale::pt_BR)));
+        assert!(!Locale(chrono::Locale::en_US).day_first());
+        assert!(Locale(chrono::Locale::sv_SE).day_first());
+        assert!("xx".parse::<Locale>().is_err());
+    }
+}
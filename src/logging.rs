@@ -0,0 +1,35 @@
+//! Diagnostics on stderr, as chatty as `-v`, `-q` or `RUST_LOG` ask for.
+use std::io::{IsTerminal, Write};
+use tracing_subscriber::EnvFilter;
+
+/// Prints diagnostics for the rest of the run: progress like fetching and
+/// using the cache by default, more with each `verbose` up to tracing every
+/// step, only warnings with one `quiet` and nothing with two. `RUST_LOG`,
+/// when set, picks the levels and targets instead, like
+/// `RUST_LOG=aoc_timeline=debug,reqwest=trace`.
+pub fn init(verbose: u8, quiet: u8) {
+    let level = match (i16::from(verbose) - i16::from(quiet)).clamp(-2, 2) {
+        -2 => "off",
+        -1 => "warn",
+        0 => "info",
+        1 => "debug",
+        _ => "trace",
+    };
+    let filter = EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| EnvFilter::new(format!("{}={level}", env!("CARGO_CRATE_NAME"))));
+    let terminal = std::io::stderr().is_terminal();
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_ansi(terminal)
+        .without_time()
+        .with_target(false)
+        .with_writer(move || {
+            let mut stderr = std::io::stderr();
+            // Over a spinner that may be drawing on the same line.
+            if terminal {
+                let _ = stderr.write_all(b"\r\x1b[2K");
+            }
+            stderr
+        })
+        .try_init();
+}
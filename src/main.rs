@@ -1,213 +1,1303 @@
 #![warn(clippy::pedantic)]
 #![allow(clippy::must_use_candidate)]
 #![allow(clippy::missing_panics_doc)]
-use chrono::{DateTime, Datelike, Duration, Local, TimeZone};
-use clap::Parser;
-use itertools::Itertools;
-use reqwest::{
-    blocking::Client,
-    header::{HeaderMap, ACCEPT, COOKIE},
-    StatusCode,
+use aoc_timeline::cache;
+use aoc_timeline::color::{self, ColorChoice};
+use aoc_timeline::config::{self, Config};
+#[cfg(feature = "sqlite")]
+use aoc_timeline::db;
+use aoc_timeline::error::{Error, Result};
+use aoc_timeline::fetch::{get_all_since, read_leaderboard, stale_copies, Previous};
+use aoc_timeline::locale::{self, Locale};
+use aoc_timeline::render::{self, Document, Format, LeaderboardReport, StarKeys, View};
+use aoc_timeline::scoring::{Counting, ScoringSystem, SortKey, SortOrder, TieBreak};
+use aoc_timeline::warnings::{Category, Warnings};
+use aoc_timeline::{
+    achievements, anonymize, baseline, canonical, combined, compare, days, diff, digest, export,
+    filter, first_blood, grid, html, layout, logging, medals, merge, notify, output, pace, pager,
+    predict, ranks, replay, sample, serve, site, stats, svg, template, tui, watch, Aoc, Board,
+    ElapsedFrom,
 };
-use securestore::{KeySource, SecretsManager};
-use serde::{Deserialize, Serialize};
-use std::path::Path;
-use std::{collections::HashMap, fs::read_to_string};
-
-const YEAR: i32 = 2024;
-const LEADERBOARDS: [i32; 2] = [649_161, 1_027_450];
-const CACHEFILE: &str = ".aoc.json";
-
-#[derive(Clone, Debug, Deserialize, Serialize)]
-struct Star {
-    get_star_ts: i64,
-}
-#[derive(Clone, Debug, Deserialize, Serialize)]
-struct Member {
-    global_score: i32,
-    name: Option<String>,
-    stars: i32,
-    id: i32,
-    last_star_ts: i64,
-    local_score: i32,
-    completion_day_level: HashMap<u32, HashMap<u32, Star>>,
-}
-#[derive(Clone, Debug, Deserialize, Serialize)]
-struct Aoc {
-    event: String,
-    owner_id: i32,
-    members: HashMap<String, Member>,
-}
-
-#[derive(Deserialize, Serialize)]
-struct CacheEntry {
-    timestamp: DateTime<Local>,
-    data: Aoc,
-}
-type Cache = HashMap<i32, CacheEntry>;
-
-struct Report {
-    timestamp: DateTime<Local>,
-    elapsed: Duration,
-    member: String,
-    star: String,
-}
-
-fn get_json(leaderbord: i32) -> Aoc {
-    let mut cache = Cache::new();
-    if std::path::Path::new(CACHEFILE).exists() {
-        cache = serde_json::from_str(&read_to_string(CACHEFILE).unwrap()).unwrap();
-        if cache.contains_key(&leaderbord) {
-            let entry = cache.get(&leaderbord).unwrap();
-            if entry.timestamp + Duration::minutes(15) > Local::now() {
-                println!("using cache");
-                return entry.data.clone();
-            }
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use clap::{ArgAction, Parser, Subcommand};
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+use std::{collections::HashMap, ops::RangeInclusive};
+use tracing::{info, warn};
+
+#[derive(Clone, Parser)]
+#[allow(clippy::struct_excessive_bools)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Read settings from this file instead of aoc-timeline.toml or the
+    /// config directory's config.toml
+    #[arg(short, long, value_name = "PATH")]
+    config: Option<PathBuf>,
+    /// Say more about fetching and the cache, -vv for every step
+    #[arg(short, long, action = ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Only print warnings on stderr, -qq for none
+    #[arg(short, long, action = ArgAction::Count)]
+    quiet: u8,
+    /// Event year, or a range like 2020..2022 (repeatable)
+    #[arg(short, long, value_parser = parse_years)]
+    year: Vec<RangeInclusive<i32>>,
+    /// Leaderboard id to report on instead of the configured ones (repeatable)
+    #[arg(short, long, value_parser = clap::value_parser!(i32).range(1..))]
+    leaderboard: Vec<i32>,
+    #[arg(short, long, action)]
+    all: bool,
+    /// Only this puzzle day, or a range like 5..10, instead of today
+    /// (repeatable)
+    #[arg(short, long, value_parser = parse_days)]
+    day: Vec<RangeInclusive<u32>>,
+    /// Only stars earned at or after this date or time, instead of today
+    #[arg(long, value_name = "TIME", value_parser = parse_start)]
+    since: Option<Time>,
+    /// Only stars earned at or before this date or time, instead of today
+    #[arg(long, value_name = "TIME", value_parser = parse_instant)]
+    until: Option<Time>,
+    /// Color the text format: auto colors a terminal unless `NO_COLOR` is set
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t)]
+    color: ColorChoice,
+    /// Print straight to the terminal instead of through $PAGER when the
+    /// report is longer than the screen
+    #[arg(long, action)]
+    no_pager: bool,
+    /// Write the report to this file instead of stdout, creating its
+    /// directory; date fields like %Y-%m-%d are filled in
+    #[arg(short, long, value_name = "PATH", conflicts_with_all = ["tui", "watching"])]
+    output: Option<PathBuf>,
+    /// Write month names and dates in this language, like de, sv-SE or fr
+    #[arg(long)]
+    locale: Option<Locale>,
+    /// Count elapsed times from the puzzle unlock or from the previous star,
+    /// by default the previous star in the timeline and the unlock in stats
+    #[arg(long, value_enum, value_name = "START")]
+    elapsed_from: Option<ElapsedFrom>,
+    /// Show times in this IANA time zone, like Europe/Oslo or UTC, rather
+    /// than the system's
+    #[arg(long, value_name = "ZONE")]
+    timezone: Option<String>,
+    /// Only stars earned in the last N calendar days, today included
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..), conflicts_with_all = ["all", "since"])]
+    last: Option<u32>,
+    #[arg(short, long, value_enum, default_value_t)]
+    format: Format,
+    /// Render the report through this Handlebars template instead of a
+    /// format, seeing the same fields as the JSON format; values are
+    /// HTML-escaped when the file name ends in .html
+    #[arg(long, value_name = "FILE", conflicts_with = "format")]
+    template: Option<PathBuf>,
+    /// Report on a generated leaderboard instead of fetching one
+    #[arg(long, action, conflicts_with = "diff")]
+    sample_data: bool,
+    /// Report on leaderboard JSON saved from adventofcode.com instead of
+    /// fetching, needing no session cookie (repeatable)
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = [
+            "sample_data", "year", "leaderboard", "cache_ttl", "diff", "new_only", "check_new",
+            "watching"
+        ]
+    )]
+    input: Vec<PathBuf>,
+    /// Fetch a cached leaderboard again after N minutes instead of the
+    /// configured time, at least 15
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(i64).range(15..))]
+    cache_ttl: Option<i64>,
+    /// Show cached leaderboards up to N minutes old at once and fetch them
+    /// after the report, rather than waiting; 0 always waits. 60 by default
+    /// on a terminal and 0 otherwise
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = [
+            "sample_data", "input", "diff", "new_only", "check_new", "watching", "tui"
+        ]
+    )]
+    max_stale: Option<u32>,
+    /// Show each member's pace towards 50 stars instead of the timeline
+    #[arg(long, action)]
+    pace: bool,
+    /// Show every member's place after each day instead of the timeline
+    #[arg(long, action, conflicts_with = "pace")]
+    ranks: bool,
+    /// Show how many solved each day and how fast, instead of the timeline
+    #[arg(long, action, conflicts_with_all = ["pace", "ranks"])]
+    day_stats: bool,
+    /// Show a member-by-day grid of stars instead of the timeline
+    #[arg(long, action, conflicts_with_all = ["pace", "ranks", "day_stats"])]
+    grid: bool,
+    /// Show a medal table for the first three on every star instead of the
+    /// timeline
+    #[arg(long, action, conflicts_with_all = ["pace", "ranks", "day_stats", "grid"])]
+    medals: bool,
+    /// Show badges for memorable moments instead of the timeline
+    #[arg(long, action, conflicts_with_all = ["pace", "ranks", "day_stats", "grid", "medals"])]
+    badges: bool,
+    /// How to score stars and rank the leaderboard
+    #[arg(long, value_enum, default_value_t)]
+    scoring: ScoringSystem,
+    /// Leave this day out of the scores, but not the timeline (repeatable)
+    #[arg(long, value_name = "DAY", value_parser = clap::value_parser!(u32).range(1..=25))]
+    exclude_day: Vec<u32>,
+    /// Order members on the same score by this, then the next one given
+    /// (repeatable); members still tied share a place
+    #[arg(long, value_enum)]
+    tie_break: Vec<TieBreak>,
+    /// Show each score as a percent of the most points possible so far, to
+    /// compare boards of different sizes
+    #[arg(long, action)]
+    normalize: bool,
+    /// Show the local score Advent of Code reports next to each score, and
+    /// the difference; not with --as-of
+    #[arg(long, action)]
+    local_score: bool,
+    /// Show who was first on the board to get each star
+    #[arg(long, action)]
+    first_blood: bool,
+    /// Use the same name for a member id on every board
+    #[arg(long, action)]
+    canonical_names: bool,
+    /// Replace every member's name with a made-up one that stays the same
+    /// between runs, for sharing reports
+    #[arg(long, action)]
+    anonymize: bool,
+    /// Add a leaderboard that counts each person once across the boards
+    #[arg(long, action)]
+    merge: bool,
+    /// Show the events of every board in one timeline, and one leaderboard
+    /// that counts each person once
+    #[arg(long, action, conflicts_with_all = ["merge", "pace", "ranks", "day_stats", "grid", "medals", "badges"])]
+    combined: bool,
+    /// Count a member as this person with --merge or --combined, as
+    /// NAME=PERSON (repeatable)
+    #[arg(long, value_parser = baseline::parse_alias)]
+    merge_alias: Vec<(String, String)>,
+    /// Write every star with its score and rank at the time as CSV, counted
+    /// as the report counts them
+    #[arg(long, value_name = "PATH")]
+    export_charts_data: Option<PathBuf>,
+    /// Draw every member's stars across the month as an SVG timeline
+    #[arg(long, value_name = "PATH")]
+    export_svg_timeline: Option<PathBuf>,
+    /// Draw every member's place after each day as an SVG bump chart
+    #[arg(long, value_name = "PATH")]
+    export_bump_chart: Option<PathBuf>,
+    /// Replay the boards up to this instant: a date (through the end of that
+    /// day), a local YYYY-MM-DDTHH:MM[:SS] time or an RFC 3339 timestamp
+    #[arg(
+        long,
+        value_name = "TIME",
+        value_parser = parse_instant,
+        conflicts_with_all = ["diff", "new_only", "check_new", "watching"]
+    )]
+    as_of: Option<Time>,
+    /// Only show what changed since the cached snapshot
+    #[arg(long, action)]
+    diff: bool,
+    #[arg(long, value_enum, default_value_t, requires = "diff")]
+    diff_format: diff::DiffFormat,
+    /// Only show the stars earned since the cached snapshot and the score
+    /// changes they caused
+    #[arg(long, action, conflicts_with_all = ["diff", "sample_data"])]
+    new_only: bool,
+    /// Exit with 0 if stars were earned since the cached snapshot and 1 if
+    /// not, for scripts; prints nothing unless --diff or --new-only is given
+    #[arg(long, action, conflicts_with_all = ["sample_data", "watching"])]
+    check_new: bool,
+    /// Only show events for this member, as NAME, NAME#ID or a regex on
+    /// either (repeatable)
+    #[arg(short, long)]
+    member: Vec<filter::MemberSpec>,
+    /// How to treat a --member name or pattern matching several members
+    #[arg(long, value_enum, default_value_t)]
+    ambiguous_members: filter::Ambiguous,
+    /// Rank by stars and solve time on boards with fewer than N members.
+    /// Points are handed out per member, so on a tiny board everyone scores
+    /// close to the maximum and the totals say little.
+    #[arg(long, value_name = "N")]
+    min_members: Option<usize>,
+    /// Only the leading N members of the leaderboard, and any picked with
+    /// --member
+    #[arg(long, value_name = "N", conflicts_with_all = ["merge", "combined"])]
+    top: Option<usize>,
+    /// List the leaderboard by this rather than by place
+    #[arg(long, value_enum, value_name = "KEY")]
+    sort: Option<SortKey>,
+    /// Which way to list the leaderboard, by default descending for scores
+    /// and stars and ascending for last star and name
+    #[arg(long, value_enum, requires = "sort")]
+    sort_order: Option<SortOrder>,
+    /// Compare members with an earlier year's leaderboard JSON
+    #[arg(long, value_name = "PATH")]
+    compare_baseline_file: Option<PathBuf>,
+    /// Match a renamed member to the baseline, as NEW=OLD (repeatable)
+    #[arg(long, value_parser = baseline::parse_alias, requires = "compare_baseline_file")]
+    baseline_alias: Vec<(String, String)>,
+    /// Keep running and print only new stars and rank moves after the report
+    #[arg(long, action, conflicts_with = "sample_data", group = "watching")]
+    watch_diff_only: bool,
+    /// Run a shell command for every new star, with the details in AOC_*
+    /// environment variables and as JSON on stdin
+    #[arg(long, value_name = "COMMAND")]
+    on_new_star: Option<String>,
+    /// Post the report to the notifiers that take one, like Slack
+    #[arg(long, action)]
+    post_report: bool,
+    /// Keep running, refresh every N minutes (15 by default) and mark new
+    /// stars with a `*`
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        default_missing_value = "15",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with_all = ["diff", "new_only", "check_new", "watch_diff_only", "tui"],
+        group = "watching"
+    )]
+    watch: Option<u64>,
+    /// Pop up a desktop notification for every new star while watching
+    #[arg(long, action, requires = "watching")]
+    notify: bool,
+    /// Browse the boards in an interactive terminal view
+    #[arg(long, action, conflicts_with_all = ["diff", "new_only", "check_new", "watch_diff_only"])]
+    tui: bool,
+}
+
+#[derive(Clone, Subcommand)]
+enum Command {
+    /// Write a static website with a page per leaderboard, day and member
+    Site {
+        /// Directory to write the pages to
+        #[arg(default_value = "site")]
+        out: PathBuf,
+    },
+    /// Serve the HTML report, refreshing the data in the background
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        listen: String,
+    },
+    /// Show one member's solves, typical times, place and missing stars
+    Stats {
+        /// NAME or NAME#ID
+        member: filter::MemberSpec,
+    },
+    /// Show who got each star first between two members, and by how much
+    Compare {
+        /// NAME or NAME#ID
+        a: filter::MemberSpec,
+        /// NAME or NAME#ID
+        b: filter::MemberSpec,
+    },
+    /// Simulate the rest of the event from each member's past solve times
+    /// and show their chances of finishing in each place
+    Predict {
+        /// Number of simulated events
+        #[arg(long, default_value_t = 10_000)]
+        runs: usize,
+        /// Seed for the simulation, the same seed gives the same result
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Email the report to the recipients in the config's [digest] section
+    Digest,
+    /// List the stars stored in the database, filtered by --year,
+    /// --leaderboard and the options below
+    #[cfg(feature = "sqlite")]
+    Query {
+        /// Only stars of this member (repeatable)
+        #[arg(long, value_name = "NAME")]
+        member: Vec<String>,
+        /// Only stars of this day (repeatable)
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=25))]
+        day: Vec<u32>,
+        /// Only part 1 or part 2 stars
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=2))]
+        part: Option<u32>,
+        /// Only stars earned on or after this day
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        since: Option<NaiveDate>,
+        /// Only stars earned on or before this day
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        until: Option<NaiveDate>,
+        #[arg(long, value_enum, default_value_t)]
+        order: db::Order,
+        /// Show at most N stars
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+    },
+    /// Look after the cache of fetched leaderboards
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Plot cumulative score per member over December
+    #[cfg(feature = "chart")]
+    Chart {
+        /// Image to write, SVG if it ends in .svg and PNG otherwise
+        #[arg(default_value = "chart.png")]
+        out: PathBuf,
+    },
+}
+
+#[derive(Clone, Subcommand)]
+enum CacheAction {
+    /// Drop leaderboards that are no longer configured or given with
+    /// --leaderboard, and with --year those of other years
+    Prune,
+}
+
+fn build(
+    aoc: &Aoc,
+    args: &Cli,
+    new: Option<&StarKeys>,
+    warnings: &mut Warnings,
+) -> Result<LeaderboardReport> {
+    let members = if args.member.is_empty() {
+        None
+    } else {
+        Some(filter::resolve(
+            &args.member,
+            &aoc.members,
+            args.ambiguous_members,
+            warnings,
+        )?)
+    };
+    let days: Vec<u32> = args.day.iter().flat_map(Clone::clone).collect();
+    let mut report = render::build(
+        aoc,
+        &render::Options {
+            all: args.all,
+            days: &days,
+            since: args.since.map(Time::utc),
+            until: args.until.map(Time::utc),
+            last: args.last,
+            members: members.as_ref(),
+            top: args.top,
+            sort: args
+                .sort
+                .map(|key| (key, args.sort_order.unwrap_or(key.default_order()))),
+            min_members: args.min_members,
+            view: if args.pace {
+                View::Pace
+            } else if args.ranks {
+                View::Ranks
+            } else if args.day_stats {
+                View::Days
+            } else if args.grid {
+                View::Grid
+            } else if args.medals {
+                View::Medals
+            } else if args.badges {
+                View::Badges
+            } else {
+                View::Timeline
+            },
+            scoring: args.scoring,
+            exclude_days: &args.exclude_day,
+            tie_breaks: &args.tie_break,
+            normalize: args.normalize,
+            local_score: args.local_score,
+            elapsed_from: args.elapsed_from.unwrap_or_default(),
+            as_of: args.as_of.map(Time::utc),
+            new,
+        },
+    );
+    for clamped in aoc_timeline::clamp_elapsed(report.events.iter_mut().map(|e| &mut e.event)) {
+        warnings.push(Category::Times, clamped);
+    }
+    Ok(report)
+}
+
+fn show(aoc: &Aoc, report: &LeaderboardReport, args: &Cli) {
+    println!("\n{}", layout::rule());
+    if let Some(pace) = &report.pace {
+        pace::print(pace);
+    } else if let Some(ranks) = &report.ranks {
+        ranks::print(ranks);
+    } else if let Some(days) = &report.days {
+        days::print(days);
+    } else if let Some(grid) = &report.grid {
+        grid::print(grid);
+    } else if let Some(medals) = &report.medals {
+        medals::print(medals);
+    } else if let Some(badges) = &report.badges {
+        achievements::print(badges);
+    } else if args.first_blood {
+        first_blood::print(&first_blood::first_blood(aoc));
+    } else {
+        render::text(report);
+    }
+}
+
+/// Parses a year or an inclusive range of years like `2020..2022`.
+fn parse_years(s: &str) -> std::result::Result<RangeInclusive<i32>, String> {
+    let parse = |y: &str| y.trim().parse::<i32>().map_err(|e| format!("{y}: {e}"));
+    match s.split_once("..") {
+        Some((from, to)) => Ok(parse(from)?..=parse(to.trim_start_matches('='))?),
+        None => parse(s).map(|year| year..=year),
+    }
+}
+
+/// Parses a puzzle day or an inclusive range of days like `5..10`.
+fn parse_days(s: &str) -> std::result::Result<RangeInclusive<u32>, String> {
+    let parse = |d: &str| match d.trim().parse::<u32>() {
+        Ok(day) if (1..=25).contains(&day) => Ok(day),
+        Ok(day) => Err(format!("{day}: days go from 1 to 25")),
+        Err(e) => Err(format!("{d}: {e}")),
+    };
+    match s.split_once("..") {
+        Some((from, to)) => Ok(parse(from)?..=parse(to.trim_start_matches('='))?),
+        None => parse(s).map(|day| day..=day),
+    }
+}
+
+/// A time from the command line, with local times kept as given until
+/// `--timezone` has been applied.
+#[derive(Clone, Copy, Debug)]
+enum Time {
+    Exact(DateTime<Utc>),
+    Local(NaiveDateTime),
+}
+
+impl Time {
+    /// The instant, taking a local time that falls in a daylight saving gap
+    /// as the hour after.
+    fn utc(self) -> DateTime<Utc> {
+        match self {
+            Time::Exact(time) => time,
+            Time::Local(time) => Local
+                .from_local_datetime(&time)
+                .earliest()
+                .or_else(|| {
+                    Local
+                        .from_local_datetime(&(time + chrono::Duration::hours(1)))
+                        .earliest()
+                })
+                .map_or_else(
+                    || Utc.from_utc_datetime(&time),
+                    |time| time.with_timezone(&Utc),
+                ),
         }
     }
-    println!("fetchin data");
-    let client = Client::new();
-    let url = format!(
-        "https://adventofcode.com/{}/leaderboard/private/view/{}.json",
-        YEAR, leaderbord
-    );
-    let key_path = Path::new(".secrets.key");
-    let sman = SecretsManager::load("secrets.json", KeySource::Path(key_path))
-        .expect("Failed to load secrets");
-    let session = sman.get("session").expect("Couldn't get session cookie");
-    let mut headers = HeaderMap::new();
-    headers.insert(COOKIE, format!("session={};", session).parse().unwrap());
-    headers.insert(ACCEPT, "application/json".parse().unwrap());
-    let res = client.get(url).headers(headers).send().unwrap();
-    if res.status() != StatusCode::OK {
-        println!("Fetch failed, cookie probably outdated.");
-        println!(
-            "Set a new cookie with 'ssclient set session <COOKIE>' ('cargo install ssclient')."
-        );
-        std::process::exit(1);
-    }
-    let text = res.text().unwrap();
-    let aoc: Aoc = serde_json::from_str(&text).unwrap();
-    cache.insert(
-        leaderbord,
-        CacheEntry {
-            timestamp: Local::now(),
-            data: aoc,
+}
+
+/// Parses `--as-of` and `--until`, with a bare date meaning the last second
+/// of that day.
+fn parse_instant(s: &str) -> std::result::Result<Time, String> {
+    time(s, NaiveTime::from_hms_opt(23, 59, 59).unwrap())
+}
+
+/// Parses `--since`, with a bare date meaning midnight at its start.
+fn parse_start(s: &str) -> std::result::Result<Time, String> {
+    time(s, NaiveTime::from_hms_opt(0, 0, 0).unwrap())
+}
+
+/// An RFC 3339 time, a local date and time, or a local date at `time`.
+fn time(s: &str, time: NaiveTime) -> std::result::Result<Time, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(s) {
+        return Ok(Time::Exact(time.with_timezone(&Utc)));
+    }
+    for format in [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ] {
+        if let Ok(time) = NaiveDateTime::parse_from_str(s, format) {
+            return Ok(Time::Local(time));
+        }
+    }
+    let date = NaiveDate::parse_from_str(s, "%Y-%m-%d").map_err(|e| format!("{s}: {e}"))?;
+    Ok(Time::Local(date.and_time(time)))
+}
+
+/// Drops the cached leaderboards that `cache prune` is not told to keep.
+fn prune(config: &Config, args: &Cli) -> Result<()> {
+    let leaderboards = if args.leaderboard.is_empty() {
+        &config.leaderboards
+    } else {
+        &args.leaderboard
+    };
+    let years = years(config, args);
+    let mut warnings = Warnings::default();
+    let removed = cache::prune(
+        config,
+        |board| {
+            leaderboards.contains(&board.leaderboard)
+                && (args.year.is_empty() || years.contains(&board.year))
         },
-    );
-    std::fs::write(CACHEFILE, serde_json::to_string(&cache).unwrap()).unwrap();
-    serde_json::from_str(&text).unwrap()
-}
-
-fn duration_string(d: Duration) -> String {
-    if d.num_days() > 0 {
-        format!(
-            "{}d {}:{:02}:{:02}",
-            d.num_days(),
-            d.num_hours() % 24,
-            d.num_minutes() % 60,
-            d.num_seconds() % 60
-        )
-    } else if d.num_hours() > 0 {
-        format!(
-            "{}:{:02}:{:02}",
-            d.num_hours(),
-            d.num_minutes() % 60,
-            d.num_seconds() % 60
-        )
+        &mut warnings,
+    )?;
+    if removed.is_empty() {
+        println!("Nothing to prune in {}", config.cache.display());
+    }
+    for key in removed {
+        println!("Removed {key}");
+    }
+    warnings.print();
+    Ok(())
+}
+
+fn years(config: &Config, args: &Cli) -> Vec<i32> {
+    if args.year.is_empty() {
+        vec![config.year]
+    } else {
+        args.year.iter().cloned().flatten().collect()
+    }
+}
+
+fn boards(config: &Config, args: &Cli) -> Vec<Board> {
+    years(config, args)
+        .into_iter()
+        .flat_map(|year| {
+            let leaderboards = if args.leaderboard.is_empty() {
+                &config.leaderboards
+            } else {
+                &args.leaderboard
+            };
+            leaderboards
+                .iter()
+                .map(move |&leaderboard| Board { year, leaderboard })
+        })
+        .collect()
+}
+
+/// Prints the report on every board in the chosen format, marking `new`
+/// stars, with `warnings` and those of the report itself in the footer.
+fn report(
+    args: &Cli,
+    boards: &[Aoc],
+    baseline: Option<&Aoc>,
+    new: Option<&StarKeys>,
+    warnings: &mut Warnings,
+) -> Result<()> {
+    let aliases: HashMap<String, String> = args.baseline_alias.iter().cloned().collect();
+    if args.combined && !matches!(args.format, Format::Text | Format::Json | Format::Markdown) {
+        return Err(Error::Usage {
+            message: "combined prints text, json or markdown".into(),
+        });
+    }
+    if args.first_blood && (args.format != Format::Text || args.combined || args.template.is_some())
+    {
+        return Err(Error::Usage {
+            message: "first blood prints text, without --combined or --template".into(),
+        });
+    }
+    let mut reports = Vec::new();
+    for aoc in boards {
+        let report = build(aoc, args, new, warnings)?;
+        if args.format == Format::Ndjson {
+            render::ndjson(&report, new.is_some());
+        }
+        if args.format == Format::Text && !args.combined && args.template.is_none() {
+            show(aoc, &report, args);
+            if let Some(baseline) = baseline {
+                baseline::print(&baseline::compare(aoc, baseline, &aliases), baseline);
+            }
+        }
+        reports.push(report);
+    }
+    let merge_aliases: HashMap<String, String> = args.merge_alias.iter().cloned().collect();
+    if let Some(path) = &args.template {
+        let standings = || merge::merge(boards, &reports, &merge_aliases);
+        let (merged, combined) = if args.combined {
+            (None, Some(combined::combine(&reports, standings())))
+        } else {
+            (args.merge.then(standings), None)
+        };
+        let document = Document {
+            leaderboards: reports,
+            merged,
+            combined,
+            warnings: warnings.collected(),
+        };
+        print!("{}", template::render(path, &document)?);
+        return Ok(());
+    }
+    if args.combined {
+        let combined = combined::combine(&reports, merge::merge(boards, &reports, &merge_aliases));
+        match args.format {
+            Format::Json => render::json(&Document {
+                leaderboards: reports,
+                merged: None,
+                combined: Some(combined),
+                warnings: warnings.collected(),
+            }),
+            Format::Markdown => print!("{}", combined::markdown(&combined)),
+            _ => combined::print(&combined),
+        }
+        if args.format != Format::Json {
+            warnings.print();
+        }
+        return Ok(());
+    }
+    let merged = args
+        .merge
+        .then(|| merge::merge(boards, &reports, &merge_aliases));
+    output(args.format, reports, merged, warnings);
+    Ok(())
+}
+
+/// Generates, reads or fetches every target board, announcing stars that
+/// are new since the cached snapshot when fetching.
+fn load(
+    config: &Config,
+    args: &Cli,
+    targets: &[Board],
+    warnings: &mut Warnings,
+) -> Result<Vec<Aoc>> {
+    let boards: Vec<Aoc> = if args.sample_data {
+        years(config, args)
+            .into_iter()
+            .map(|year| {
+                let mut aoc = sample::leaderboard(year, sample::SEED);
+                filter::restrict(&mut aoc, &config.members);
+                aoc
+            })
+            .collect()
+    } else if !args.input.is_empty() {
+        args.input
+            .iter()
+            .map(|path| {
+                let mut aoc = read_leaderboard(path)?;
+                filter::restrict(&mut aoc, &config.members);
+                Ok(aoc)
+            })
+            .collect::<Result<_>>()?
     } else {
-        format!("{:02}:{:02}", d.num_minutes() % 60, d.num_seconds() % 60)
-    }
-}
-
-fn timeline(members: &HashMap<String, Member>) -> Vec<Report> {
-    let mut timeline = Vec::<Report>::new();
-    for member in members.values() {
-        for dayno in member.completion_day_level.keys().sorted() {
-            let day = &member.completion_day_level[dayno];
-            let mut start = Local
-                .with_ymd_and_hms(YEAR, 12, *dayno, 6, 0, 0)
-                .single()
-                .unwrap();
-            for star in 1..=2 {
-                if day.contains_key(&star) {
-                    let solvetime = Local
-                        .timestamp_opt(day[&star].get_star_ts, 0)
-                        .single()
-                        .unwrap();
-                    timeline.push(Report {
-                        timestamp: solvetime,
-                        elapsed: solvetime - start,
-                        member: if let Some(name) = member.name.clone() {
-                            name
-                        } else {
-                            format!("Anonymous#{}", member.id)
-                        },
-                        star: format!("{:02}-{}", dayno, star),
-                    });
-                    start = solvetime;
-                }
+        refresh(config, targets, counting(args), warnings)?
+    };
+    Ok(prepare(config, args, boards))
+}
+
+/// Fetches `boards` without the members the config leaves out, with what
+/// the cache held for each before.
+fn fetch(
+    config: &Config,
+    boards: &[Board],
+    warnings: &mut Warnings,
+) -> Result<Vec<(Aoc, Previous)>> {
+    let mut boards = get_all_since(config, boards, warnings)?;
+    for (aoc, previous) in &mut boards {
+        filter::restrict(aoc, &config.members);
+        if let Previous::Older(old) = previous {
+            filter::restrict(old, &config.members);
+        }
+    }
+    Ok(boards)
+}
+
+/// Fetches `targets`, announcing stars that are new since the cached
+/// snapshot with points from `counting`.
+fn refresh(
+    config: &Config,
+    targets: &[Board],
+    counting: Counting,
+    warnings: &mut Warnings,
+) -> Result<Vec<Aoc>> {
+    let fetched = fetch(config, targets, warnings)?;
+    let diffs: Vec<_> = targets
+        .iter()
+        .zip(&fetched)
+        .filter_map(|(&board, (aoc, previous))| match previous {
+            Previous::Missing => None,
+            Previous::Same => Some(diff::diff(board, aoc, aoc, counting)),
+            Previous::Older(old) => Some(diff::diff(board, old, aoc, counting)),
+        })
+        .collect();
+    notify::send(config, &diffs, warnings);
+    Ok(fetched.into_iter().map(|(aoc, _)| aoc).collect())
+}
+
+/// Names the members and replays the boards as `args` ask.
+fn prepare(config: &Config, args: &Cli, mut boards: Vec<Aoc>) -> Vec<Aoc> {
+    rename(config, args, &mut boards);
+    if let Some(at) = args.as_of.map(Time::utc) {
+        for aoc in &mut boards {
+            replay::as_of(aoc, at);
+        }
+    }
+    boards
+}
+
+/// The cached boards to report on while they are fetched again, as long as
+/// the report ends and none is older than `--max-stale`, or an hour on an
+/// `interactive` terminal.
+fn stale(
+    config: &Config,
+    args: &Cli,
+    interactive: bool,
+    warnings: &mut Warnings,
+) -> Result<Option<Vec<Aoc>>> {
+    let ends = !args.sample_data
+        && args.input.is_empty()
+        && !args.tui
+        && args.watch.is_none()
+        && !args.watch_diff_only
+        && !matches!(args.command, Some(Command::Serve { .. }));
+    let max_stale = args.max_stale.unwrap_or(if interactive { 60 } else { 0 });
+    if !ends || max_stale == 0 {
+        return Ok(None);
+    }
+    let max_age = chrono::Duration::minutes(max_stale.into());
+    let Some(mut boards) = stale_copies(config, &boards(config, args), max_age, warnings)? else {
+        return Ok(None);
+    };
+    for aoc in &mut boards {
+        filter::restrict(aoc, &config.members);
+    }
+    Ok(Some(prepare(config, args, boards)))
+}
+
+/// The points the report counts, for everything else that shows points.
+fn counting(args: &Cli) -> Counting<'_> {
+    Counting {
+        scoring: args.scoring.scoring(),
+        exclude_days: &args.exclude_day,
+    }
+}
+
+/// Gives members their nicknames from the config and the names
+/// `--canonical-names` and `--anonymize` ask for.
+fn rename(config: &Config, args: &Cli, boards: &mut [Aoc]) {
+    if args.canonical_names {
+        canonical::apply(boards);
+    }
+    canonical::nickname(boards, &config.nicknames);
+    if args.anonymize {
+        anonymize::apply(boards);
+    }
+}
+
+/// Prints what the text format did not print as it went.
+fn output(
+    format: Format,
+    reports: Vec<LeaderboardReport>,
+    merged: Option<Vec<merge::Person>>,
+    warnings: &Warnings,
+) {
+    match format {
+        Format::Text => {
+            if let Some(merged) = &merged {
+                merge::print(merged);
             }
+            warnings.print();
+        }
+        Format::Json => render::json(&Document {
+            leaderboards: reports,
+            merged,
+            combined: None,
+            warnings: warnings.collected(),
+        }),
+        Format::Ndjson => warnings.print(),
+        Format::Csv => {
+            print!("{}", render::csv(&reports));
+            warnings.print();
+        }
+        Format::Markdown => {
+            let mut tables: Vec<_> = reports.iter().map(render::markdown).collect();
+            tables.extend(merged.as_deref().map(merge::markdown));
+            print!("{}", tables.join("\n"));
+            warnings.print();
+        }
+        Format::Html => {
+            print!("{}", html::report(&reports));
+            warnings.print();
         }
     }
-    timeline.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
-    timeline
 }
 
-fn report(leaderbord: i32, all: bool) {
-    println!("\n{}", String::from_utf8(vec![b'#'; 70]).unwrap());
-    let aoc = get_json(leaderbord);
-    let max_score = aoc.members.len();
+/// Midnight at the start of `date`, local time.
+#[cfg(feature = "sqlite")]
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .unwrap()
+        .with_timezone(&Utc)
+}
 
-    let mut day = String::new();
-    let mut score: HashMap<String, usize> = HashMap::new();
-    let mut total_score: HashMap<String, usize> = HashMap::new();
-    let today = chrono::offset::Local::now().day();
+#[cfg(feature = "sqlite")]
+fn query(config: &Config, args: &Cli) -> Result<()> {
+    let Some(Command::Query {
+        member,
+        day,
+        part,
+        since,
+        until,
+        order,
+        limit,
+    }) = &args.command
+    else {
+        return Ok(());
+    };
+    let Some(database) = &config.database else {
+        return Err(Error::Usage {
+            message: "query needs a database in the config".into(),
+        });
+    };
+    let stars = db::query(
+        &db::open(database)?,
+        &db::Query {
+            years: args.year.iter().cloned().flatten().collect(),
+            leaderboards: args.leaderboard.clone(),
+            members: member.clone(),
+            days: day.clone(),
+            part: *part,
+            since: since.map(midnight),
+            until: until.map(|until| midnight(until + chrono::Duration::days(1))),
+            order: *order,
+            limit: *limit,
+        },
+    )?;
+    db::print(&stars, args.format);
+    Ok(())
+}
 
-    for event in timeline(&aoc.members) {
-        let event_day = format!("{}", event.timestamp.format("%B %e"));
+fn main() -> ExitCode {
+    let mut args = Cli::parse();
+    match run(&mut args) {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {e}");
+            // 1 means nothing new to --check-new.
+            match e.exit_code() {
+                1 if args.check_new => ExitCode::from(8),
+                code => ExitCode::from(code),
+            }
+        }
+    }
+}
 
-        score
-            .entry(event.star.clone())
-            .and_modify(|e| *e -= 1)
-            .or_insert(max_score);
+/// Prints `stats <member>` for every board the member is on.
+fn member_stats(
+    args: &Cli,
+    boards: &[Aoc],
+    member: &filter::MemberSpec,
+    warnings: &mut Warnings,
+) -> Result<()> {
+    let now = args.as_of.map_or_else(Utc::now, Time::utc);
+    let mut found = Vec::new();
+    for aoc in boards {
+        let ids = filter::resolve(
+            std::slice::from_ref(member),
+            &aoc.members,
+            args.ambiguous_members,
+            warnings,
+        )?;
+        found.extend(
+            aoc.members
+                .values()
+                .filter(|m| ids.contains(&m.id))
+                .map(|m| {
+                    stats::stats(
+                        aoc,
+                        m,
+                        now,
+                        args.elapsed_from.unwrap_or(ElapsedFrom::Unlock),
+                        counting(args),
+                    )
+                }),
+        );
+    }
+    if found.is_empty() {
+        return Err(Error::Usage {
+            message: format!("no member '{}' on these leaderboards", member.name),
+        });
+    }
+    match args.format {
+        Format::Text => found.iter().for_each(stats::print),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&found).unwrap()),
+        _ => {
+            return Err(Error::Usage {
+                message: "stats prints text or json".into(),
+            })
+        }
+    }
+    warnings.print();
+    Ok(())
+}
+
+/// Prints `predict` for every board.
+fn prediction(args: &Cli, boards: &[Aoc], runs: usize, seed: u64) -> Result<()> {
+    let now = args.as_of.map_or_else(Utc::now, Time::utc);
+    let predictions: Vec<_> = boards
+        .iter()
+        .map(|aoc| predict::predict(aoc, now, runs, seed, counting(args)))
+        .collect();
+    match args.format {
+        Format::Text => predictions.iter().for_each(predict::print),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&predictions).unwrap()),
+        _ => {
+            return Err(Error::Usage {
+                message: "predict prints text or json".into(),
+            })
+        }
+    }
+    Ok(())
+}
+
+/// Prints `compare <a> <b>` for every board both members are on.
+fn head_to_head(
+    args: &Cli,
+    boards: &[Aoc],
+    a: &filter::MemberSpec,
+    b: &filter::MemberSpec,
+) -> Result<()> {
+    let mut found = Vec::new();
+    for aoc in boards {
+        if let (Some(a), Some(b)) = (compare::find(aoc, a)?, compare::find(aoc, b)?) {
+            found.push(compare::compare(aoc, a, b, counting(args)));
+        }
+    }
+    if found.is_empty() {
+        return Err(Error::Usage {
+            message: format!(
+                "'{}' and '{}' do not share any of these leaderboards",
+                a.name, b.name
+            ),
+        });
+    }
+    match args.format {
+        Format::Text => found.iter().for_each(compare::print),
+        Format::Json => println!("{}", serde_json::to_string_pretty(&found).unwrap()),
+        _ => {
+            return Err(Error::Usage {
+                message: "compare prints text or json".into(),
+            })
+        }
+    }
+    Ok(())
+}
 
-        let star_score = score[&event.star];
-        if all || event.timestamp.day() == today {
-            if event_day != day {
-                println!("\n{}", event_day);
-                day = event_day;
+/// Prints what changed since the cached snapshot, for --diff and
+/// --new-only, and tells whether any stars are new, for --check-new.
+fn changes(config: &Config, args: &Cli) -> Result<bool> {
+    let mut warnings = Warnings::default();
+    let mut diffs = Vec::new();
+    let targets = boards(config, args);
+    for (&board, (fresh, previous)) in targets.iter().zip(fetch(config, &targets, &mut warnings)?) {
+        match previous {
+            Previous::Missing => warnings.push(
+                Category::Cache,
+                format!(
+                    "no previous snapshot of {} ({}) to diff against",
+                    board.leaderboard, board.year
+                ),
+            ),
+            Previous::Same => {
+                let mut same = [fresh];
+                rename(config, args, &mut same);
+                diffs.push(diff::diff(board, &same[0], &same[0], counting(args)));
+            }
+            Previous::Older(old) => {
+                let mut pair = [old, fresh];
+                rename(config, args, &mut pair);
+                diffs.push(diff::diff(board, &pair[0], &pair[1], counting(args)));
             }
-            println!(
-                "  {} {:25}\t{} [{}] ({})",
-                event.timestamp.time(),
-                event.member,
-                event.star,
-                star_score,
-                duration_string(event.elapsed)
-            );
         }
+    }
+    if args.new_only {
+        diff::print_new_stars(&diffs);
+    } else if args.diff {
+        diff::print(&diffs, args.diff_format);
+    }
+    warnings.print();
+    Ok(diffs.iter().any(|diff| !diff.new_stars.is_empty()))
+}
 
-        total_score
-            .entry(event.member.clone())
-            .and_modify(|e| *e += star_score)
-            .or_insert(star_score);
+/// Writes the files the `--export-*` options ask for.
+fn export(args: &Cli, boards: &[Aoc]) -> Result<()> {
+    if let Some(path) = &args.export_charts_data {
+        export::write_charts_data(path, boards, counting(args), |aoc| {
+            if args.member.is_empty() {
+                return Ok(None);
+            }
+            // The report has already warned about these.
+            filter::resolve(
+                &args.member,
+                &aoc.members,
+                args.ambiguous_members,
+                &mut Warnings::default(),
+            )
+            .map(Some)
+        })?;
+    }
+    if let Some(path) = &args.export_svg_timeline {
+        svg::write_gantt(path, boards)?;
     }
-    println!("\nLeaderboard:");
-    for (name, total) in total_score.iter().sorted_by(|a, b| b.1.cmp(a.1)) {
-        println!("  {:25} {}", name, total);
+    if let Some(path) = &args.export_bump_chart {
+        svg::write_bump_chart(path, boards, counting(args))?;
     }
+    Ok(())
 }
 
-#[derive(Parser)]
-struct Cli {
-    #[arg(short, long, action)]
-    all: bool,
+/// Makes `zone` the local time zone for the rest of the run.
+fn use_timezone(zone: &str) -> Result<()> {
+    let dirs: Vec<PathBuf> = std::env::var_os("TZDIR")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(
+            ["/usr/share/zoneinfo", "/share/zoneinfo", "/etc/zoneinfo"]
+                .into_iter()
+                .map(PathBuf::from),
+        )
+        .collect();
+    let known = zone == "UTC" || dirs.iter().any(|dir| dir.join(zone).is_file());
+    if !known {
+        return Err(Error::Usage {
+            message: format!(
+                "unknown time zone '{zone}', not found in {}",
+                dirs.iter()
+                    .map(|dir| dir.display().to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        });
+    }
+    std::env::set_var("TZ", zone);
+    Ok(())
+}
+
+/// Applies the time zone and locale from the command line or the config.
+fn localize(args: &Cli, config: &Config) -> Result<()> {
+    if let Some(zone) = args.timezone.as_ref().or(config.timezone.as_ref()) {
+        use_timezone(zone)?;
+    }
+    let locale = match (args.locale, &config.locale) {
+        (Some(locale), _) => locale,
+        (None, Some(locale)) => locale.parse().map_err(|message| Error::Usage { message })?,
+        (None, None) => Locale::default(),
+    };
+    locale::set(locale);
+    Ok(())
+}
+
+/// Whether the run prints a report that ends, rather than keeping the
+/// terminal.
+fn paged(args: &Cli) -> bool {
+    !args.no_pager
+        && !args.tui
+        && args.watch.is_none()
+        && !args.watch_diff_only
+        && !matches!(args.command, Some(Command::Serve { .. }))
+}
+
+/// Points stdout at `--output` or a pager, then sets colors and the layout
+/// for where it goes.
+fn terminal(args: &Cli) -> Result<Option<pager::Pager>> {
+    if let Some(path) = &args.output {
+        let now = args.as_of.map_or_else(Utc::now, Time::utc);
+        output::redirect(&output::dated(path, now.with_timezone(&Local))?)?;
+    }
+    color::set(args.color);
+    layout::detect();
+    Ok(paged(args).then(pager::start).flatten())
+}
+
+/// Refuses to show Advent of Code's local score where it cannot be right:
+/// it is reported for now and for every day, so it says nothing about the
+/// board `--as-of` an earlier time and `--scoring official` cannot leave days
+/// out.
+fn official(args: &Cli) -> Result<()> {
+    let shown = args.local_score || args.scoring == ScoringSystem::Official;
+    if shown && args.as_of.is_some() {
+        return Err(Error::Usage {
+            message: "local scores are today's and cannot be shown --as-of another time".into(),
+        });
+    }
+    if args.scoring == ScoringSystem::Official && !args.exclude_day.is_empty() {
+        return Err(Error::Usage {
+            message: "official scoring counts every day, without --exclude-day or exclude_days"
+                .into(),
+        });
+    }
+    Ok(())
+}
+
+fn run(args: &mut Cli) -> Result<ExitCode> {
+    logging::init(args.verbose, args.quiet);
+    let mut config = config::load(args.config.as_deref())?;
+    localize(args, &config)?;
+    args.exclude_day.extend(&config.exclude_days);
+    let merge_aliases = std::mem::take(&mut args.merge_alias);
+    args.merge_alias = config
+        .aliases
+        .clone()
+        .into_iter()
+        .chain(merge_aliases)
+        .collect();
+    if args.tie_break.is_empty() {
+        args.tie_break.clone_from(&config.tie_break);
+    }
+    let args: &Cli = args;
+    official(args)?;
+    if let Some(command) = &args.on_new_star {
+        config.notify.exec = Some(command.clone());
+    }
+    // Pop-ups are for watching, not for cron jobs and other one-shot runs.
+    let watching = args.watch.is_some() || args.watch_diff_only;
+    config.notify.desktop = watching && (config.notify.desktop || args.notify);
+    if let Some(ttl) = args.cache_ttl {
+        config.cache_ttl = Some(ttl);
+        config.leaderboard_cache_ttl.clear();
+    }
+    let interactive = std::io::stdout().is_terminal();
+    let _pager = terminal(args)?;
+    #[cfg(feature = "sqlite")]
+    if matches!(args.command, Some(Command::Query { .. })) {
+        return query(&config, args).map(|()| ExitCode::SUCCESS);
+    }
+    if matches!(args.command, Some(Command::Cache { .. })) {
+        return prune(&config, args).map(|()| ExitCode::SUCCESS);
+    }
+    if args.diff || args.new_only || args.check_new {
+        let new = changes(&config, args)?;
+        return Ok(if new || !args.check_new {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        });
+    }
+    let mut warnings = Warnings::default();
+    let shown = stale(&config, args, interactive, &mut warnings)?;
+    let revalidate = shown.is_some();
+    report_boards(&config, args, shown, &mut warnings)?;
+    if revalidate {
+        let mut warnings = Warnings::default();
+        if let Err(e) = refresh(
+            &config,
+            &boards(&config, args),
+            counting(args),
+            &mut warnings,
+        ) {
+            warn!("{e}");
+        }
+        warnings.print();
+    }
+    Ok(ExitCode::SUCCESS)
 }
 
-fn main() {
-    let args = Cli::parse();
-    for leaderbord in LEADERBOARDS {
-        report(leaderbord, args.all);
+/// Loads the boards, unless `shown` already holds them, and prints, serves
+/// or writes what `args` asks for, with `warnings` and those of loading in
+/// the footer.
+fn report_boards(
+    config: &Config,
+    args: &Cli,
+    shown: Option<Vec<Aoc>>,
+    warnings: &mut Warnings,
+) -> Result<()> {
+    let targets = boards(config, args);
+    let boards = match shown {
+        Some(boards) => boards,
+        None => load(config, args, &targets, warnings)?,
+    };
+    // Modes that keep running print no footer, so they log what loading
+    // the boards again ran into.
+    let reload = || {
+        let mut warnings = Warnings::default();
+        let boards = load(config, args, &targets, &mut warnings);
+        warnings.log();
+        boards
+    };
+    if args.tui {
+        warnings.log();
+        // Every star, to filter by day and member.
+        let args = Cli {
+            all: true,
+            ..args.clone()
+        };
+        return tui::run(&boards, reload, |aoc| {
+            build(aoc, &args, None, &mut Warnings::default())
+        });
+    }
+    match &args.command {
+        Some(Command::Site { out }) => {
+            // Every page, not only today's stars.
+            let args = Cli {
+                all: true,
+                ..args.clone()
+            };
+            site::write_site(out, &boards, counting(&args), |aoc| {
+                build(aoc, &args, None, &mut Warnings::default())
+            })?;
+            info!("wrote {}", out.join("index.html").display());
+            warnings.print();
+            return Ok(());
+        }
+        Some(Command::Serve { listen }) => {
+            warnings.log();
+            let every = watch::poll(config, &targets);
+            let build = |aoc: &Aoc| build(aoc, args, None, &mut Warnings::default());
+            return serve::serve(listen, boards, counting(args), every, reload, build);
+        }
+        Some(Command::Stats { member }) => return member_stats(args, &boards, member, warnings),
+        Some(Command::Compare { a, b }) => {
+            head_to_head(args, &boards, a, b)?;
+            warnings.print();
+            return Ok(());
+        }
+        Some(Command::Predict { runs, seed }) => {
+            prediction(args, &boards, *runs, *seed)?;
+            warnings.print();
+            return Ok(());
+        }
+        Some(Command::Digest) => {
+            let reports = boards
+                .iter()
+                .map(|aoc| build(aoc, args, None, warnings))
+                .collect::<Result<Vec<_>>>()?;
+            digest::send(config, &reports)?;
+            warnings.print();
+            return Ok(());
+        }
+        #[cfg(feature = "sqlite")]
+        Some(Command::Query { .. }) => unreachable!("handled before loading"),
+        Some(Command::Cache { .. }) => unreachable!("handled before loading"),
+        #[cfg(feature = "chart")]
+        Some(Command::Chart { out }) => {
+            aoc_timeline::chart::write_chart(out, &boards, counting(args))?;
+            info!("wrote {}", out.display());
+            warnings.print();
+            return Ok(());
+        }
+        None => {}
+    }
+    let baseline = args
+        .compare_baseline_file
+        .as_deref()
+        .map(read_leaderboard)
+        .transpose()?;
+    if args.post_report {
+        // Before the report, so failures show in its warnings.
+        let reports = boards
+            .iter()
+            .map(|aoc| build(aoc, args, None, &mut Warnings::default()))
+            .collect::<Result<Vec<_>>>()?;
+        notify::post_reports(config, &reports, warnings);
+    }
+    report(args, &boards, baseline.as_ref(), None, warnings)?;
+    export(args, &boards)?;
+    if let Some(minutes) = args.watch {
+        watch::redraw(
+            Duration::from_mins(minutes),
+            &boards,
+            |warnings| load(config, args, &targets, warnings),
+            |boards, new, warnings| report(args, boards, baseline.as_ref(), Some(new), warnings),
+        );
+    }
+    if args.watch_diff_only {
+        watch::diff_only(config, &targets, counting(args), boards, |boards| {
+            rename(config, args, boards);
+        });
     }
+    Ok(())
 }
@@ -0,0 +1,88 @@
+//! Gold, silver and bronze for the first three on every star.
+use crate::layout;
+use crate::{timeline, Aoc, Report};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The first three to get one star. Members who got it in the same second
+/// share a place.
+#[derive(Debug, Serialize)]
+pub struct Podium {
+    pub day: u32,
+    pub part: u32,
+    pub places: Vec<(usize, Report)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MedalCount {
+    pub member: String,
+    pub gold: usize,
+    pub silver: usize,
+    pub bronze: usize,
+}
+
+/// Podiums for every star anyone has, in day and part order.
+pub fn podiums(aoc: &Aoc) -> Vec<Podium> {
+    timeline(aoc)
+        .into_iter()
+        .into_group_map_by(|event| (event.day(), event.part()))
+        .into_iter()
+        .sorted_by_key(|(star, _)| *star)
+        .map(|((day, part), events)| {
+            // The timeline is in time order, so each group is too.
+            let times: Vec<_> = events.iter().map(|event| event.timestamp).collect();
+            let places = events
+                .into_iter()
+                .map(|event| {
+                    let place = 1 + times.iter().filter(|t| **t < event.timestamp).count();
+                    (place, event)
+                })
+                .take_while(|(place, _)| *place <= 3)
+                .collect();
+            Podium { day, part, places }
+        })
+        .collect()
+}
+
+/// Medals per member, ranked like the Olympics: most golds, then silvers,
+/// then bronzes.
+pub fn medals(podiums: &[Podium]) -> Vec<MedalCount> {
+    let mut counts: HashMap<&str, [usize; 3]> = HashMap::new();
+    for podium in podiums {
+        for (place, event) in &podium.places {
+            counts.entry(&event.member).or_default()[place - 1] += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .sorted_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.cmp(b)))
+        .map(|(member, [gold, silver, bronze])| MedalCount {
+            member: member.to_string(),
+            gold,
+            silver,
+            bronze,
+        })
+        .collect()
+}
+
+pub fn print(medals: &[MedalCount]) {
+    println!("\nMedals (first three on every star):");
+    println!(
+        "  {:width$} {:>4} {:>6} {:>6}",
+        "",
+        "Gold",
+        "Silver",
+        "Bronze",
+        width = layout::name_width()
+    );
+    for count in medals {
+        println!(
+            "  {} {:>4} {:>6} {:>6}",
+            layout::name(&count.member),
+            count.gold,
+            count.silver,
+            count.bronze
+        );
+    }
+}
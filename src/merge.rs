@@ -0,0 +1,158 @@
+//! One entry per person across several boards, for members who are on more
+//! than one.
+use crate::canonical::canonical_names;
+use crate::layout;
+use crate::render::{cell, LeaderboardReport};
+use crate::{display_names, Aoc};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// A person's score on one board.
+#[derive(Debug, Serialize)]
+pub struct BoardScore {
+    pub year: i32,
+    pub leaderboard: i32,
+    /// The name on this board.
+    pub member: String,
+    pub member_id: i32,
+    pub points: usize,
+}
+
+/// Everything one person scored, counted once however many boards they are
+/// on.
+#[derive(Debug, Serialize)]
+pub struct Person {
+    /// Shared with people on the same points.
+    pub rank: usize,
+    pub member: String,
+    /// The sum of the points on every board.
+    pub points: usize,
+    pub boards: Vec<BoardScore>,
+}
+
+/// Counts each person on `boards` once, adding up the standings of the
+/// matching `reports`. Members with the same id are the same person, and so
+/// are members whose name, `Anonymous#ID` or id `aliases` maps to the same
+/// person, along with the member of that name.
+pub fn merge(
+    boards: &[Aoc],
+    reports: &[LeaderboardReport],
+    aliases: &HashMap<String, String>,
+) -> Vec<Person> {
+    let canonical = canonical_names(boards);
+    let targets: HashSet<&String> = aliases.values().collect();
+    let mut people: HashMap<String, (String, Vec<BoardScore>)> = HashMap::new();
+    for (aoc, report) in boards.iter().zip(reports) {
+        let names = display_names(&aoc.members);
+        let ids: HashMap<&str, i32> = names
+            .iter()
+            .map(|(id, name)| (name.as_str(), *id))
+            .collect();
+        for score in &report.standings {
+            let Some(&id) = ids.get(score.member.as_str()) else {
+                continue;
+            };
+            let alias = aliases
+                .get(&score.member)
+                .or_else(|| aliases.get(&id.to_string()));
+            let name = match alias {
+                Some(person) => person.clone(),
+                None => canonical
+                    .get(&id)
+                    .cloned()
+                    .unwrap_or_else(|| score.member.clone()),
+            };
+            // Whoever an alias points to is the person the alias joins.
+            let key = if alias.is_some() || targets.contains(&name) {
+                format!("alias:{name}")
+            } else {
+                format!("id:{id}")
+            };
+            people
+                .entry(key)
+                .or_insert_with(|| (name, Vec::new()))
+                .1
+                .push(BoardScore {
+                    year: report.year,
+                    leaderboard: report.leaderboard,
+                    member: score.member.clone(),
+                    member_id: id,
+                    points: score.points,
+                });
+        }
+    }
+    let mut merged: Vec<Person> = people
+        .into_values()
+        .map(|(member, boards)| Person {
+            rank: 0,
+            points: boards.iter().map(|board| board.points).sum(),
+            member,
+            boards,
+        })
+        .sorted_by(|a, b| {
+            b.points
+                .cmp(&a.points)
+                .then_with(|| a.member.cmp(&b.member))
+        })
+        .collect();
+    for i in 0..merged.len() {
+        merged[i].rank = if i > 0 && merged[i - 1].points == merged[i].points {
+            merged[i - 1].rank
+        } else {
+            i + 1
+        };
+    }
+    merged
+}
+
+/// `2024/12345 310, 2024/67890 as alice-work 95` for a person's boards.
+fn breakdown(person: &Person) -> String {
+    person
+        .boards
+        .iter()
+        .map(|board| {
+            let name = if board.member == person.member {
+                String::new()
+            } else {
+                format!(" as {}", board.member)
+            };
+            format!(
+                "{}/{}{name} {}",
+                board.year, board.leaderboard, board.points
+            )
+        })
+        .join(", ")
+}
+
+pub fn print(people: &[Person]) {
+    println!("\nAcross leaderboards:");
+    for person in people {
+        println!(
+            "  {:>3}. {} {:5}  ({})",
+            person.rank,
+            layout::name(&person.member),
+            person.points,
+            breakdown(person)
+        );
+    }
+}
+
+pub fn markdown(people: &[Person]) -> String {
+    let mut md = String::from(
+        "## Across leaderboards\n\n| # | Member | Points | Boards |\n|--:|---|--:|---|\n",
+    );
+    for person in people {
+        writeln!(
+            md,
+            "| {} | {} | {} | {} |",
+            person.rank,
+            cell(&person.member),
+            person.points,
+            cell(&breakdown(person))
+        )
+        .unwrap();
+    }
+    md
+}
@@ -0,0 +1,63 @@
+//! Per-member gauges in the Prometheus text exposition format.
+use crate::scoring::Counting;
+use crate::{display_names, Aoc};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+/// Escapes a label value: backslash, double quote and newline.
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Stars, points and the time of the last star for every member on every
+/// board, labelled by year, leaderboard, member name and id. Points are
+/// those of `counting`.
+pub fn prometheus(boards: &[Aoc], counting: Counting) -> String {
+    let gauges = [
+        ("aoc_member_stars", "Stars earned by the member."),
+        (
+            "aoc_member_score",
+            "Points scored on the private leaderboard.",
+        ),
+        (
+            "aoc_member_last_star_timestamp_seconds",
+            "Unix time of the member's latest star, 0 before the first.",
+        ),
+    ];
+    let mut samples: [Vec<String>; 3] = Default::default();
+    for aoc in boards {
+        let names = display_names(&aoc.members);
+        let mut points: HashMap<i32, usize> = HashMap::new();
+        for (event, star_points) in counting.scored(aoc) {
+            *points.entry(event.member_id).or_default() += star_points;
+        }
+        for member in aoc.members.values() {
+            let labels = format!(
+                "year=\"{}\",leaderboard=\"{}\",member=\"{}\",member_id=\"{}\"",
+                aoc.year(),
+                aoc.owner_id,
+                label(&names[&member.id]),
+                member.id
+            );
+            let values = [
+                member.stars.to_string(),
+                points.get(&member.id).copied().unwrap_or(0).to_string(),
+                member.last_star_ts.to_string(),
+            ];
+            for (samples, value) in samples.iter_mut().zip(values) {
+                samples.push(format!("{{{labels}}} {value}"));
+            }
+        }
+    }
+    let mut text = String::new();
+    for ((name, help), samples) in gauges.iter().zip(samples) {
+        writeln!(text, "# HELP {name} {help}\n# TYPE {name} gauge").unwrap();
+        for sample in samples {
+            writeln!(text, "{name}{sample}").unwrap();
+        }
+    }
+    text
+}
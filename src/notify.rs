@@ -0,0 +1,658 @@
+//! Announcements of new stars, sent to whatever the `[notify]` section of
+//! the config sets up.
+use crate::config::{Config, SlackWebhook};
+use crate::diff::{Diff, NewStar};
+use crate::error::{Error, Result};
+use crate::fetch::secret;
+use crate::render::{LeaderboardReport, ScoredEvent};
+use crate::warnings::{Category, Warnings};
+use crate::{runtime, unlock};
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Proxy};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::future::Future;
+use std::io::Write;
+use std::pin::Pin;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+/// A message on its way, to await on the shared runtime.
+pub type Sending<'a> = Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>>;
+
+/// Somewhere new stars can be announced.
+pub trait Notifier: Send + Sync {
+    /// Shown when sending fails.
+    fn name(&self) -> &'static str;
+    /// Announces the new stars of one board.
+    ///
+    /// # Errors
+    ///
+    /// When the message cannot be delivered.
+    fn notify<'a>(&'a self, diff: &'a Diff) -> Sending<'a>;
+    /// Posts a whole report, for notifiers that have a use for one.
+    ///
+    /// # Errors
+    ///
+    /// When the message cannot be delivered.
+    fn report<'a>(&'a self, _report: &'a LeaderboardReport) -> Sending<'a> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{n}{suffix}")
+}
+
+/// Joins lines into as few messages of at most `limit` bytes as possible.
+fn join_lines(lines: impl IntoIterator<Item = String>, limit: usize) -> Vec<String> {
+    let mut messages: Vec<String> = Vec::new();
+    for line in lines {
+        match messages.last_mut() {
+            Some(message) if message.len() + 1 + line.len() <= limit => {
+                message.push('\n');
+                message.push_str(&line);
+            }
+            _ => messages.push(line),
+        }
+    }
+    messages
+}
+
+/// Like "Alice solved 07-2 in 1:23:45, now 3rd place".
+pub fn message(star: &NewStar) -> String {
+    format!(
+        "{} solved {} in {}, now {} place",
+        star.member,
+        star.star,
+        star.elapsed_string(),
+        ordinal(star.rank)
+    )
+}
+
+/// Posts to a Discord webhook, one line per star.
+pub struct Discord {
+    pub webhook: String,
+    pub client: Client,
+}
+
+/// Discord rejects messages longer than this.
+const DISCORD_LIMIT: usize = 2000;
+
+impl Notifier for Discord {
+    fn name(&self) -> &'static str {
+        "Discord"
+    }
+
+    fn notify<'a>(&'a self, diff: &'a Diff) -> Sending<'a> {
+        Box::pin(async move {
+            for content in join_lines(diff.new_stars.iter().map(message), DISCORD_LIMIT) {
+                self.client
+                    .post(&self.webhook)
+                    .json(&json!({ "content": content }))
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Posts Block Kit messages to a Slack incoming webhook.
+pub struct Slack {
+    pub webhook: SlackWebhook,
+    pub client: Client,
+}
+
+/// Slack allows this many blocks in a message.
+const SLACK_BLOCKS: usize = 50;
+
+/// Escapes the characters Slack reads as control sequences in mrkdwn text,
+/// so a member named `<!channel>` does not mention everyone.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn mrkdwn(text: impl Into<String>) -> Value {
+    json!({ "type": "mrkdwn", "text": text.into() })
+}
+
+/// A new star's member and time, side by side.
+fn star_section(star: &NewStar) -> Value {
+    json!({
+        "type": "section",
+        "fields": [
+            mrkdwn(format!("*{}*\n{}", escape(&star.member), star.star)),
+            mrkdwn(format!(
+                "{} (+{})\nnow {} with {}",
+                star.elapsed_string(),
+                star.points,
+                ordinal(star.rank),
+                star.score
+            )),
+        ],
+    })
+}
+
+fn header(text: &str) -> Value {
+    json!({ "type": "header", "text": { "type": "plain_text", "text": text } })
+}
+
+impl Slack {
+    fn wants(&self, leaderboard: i32) -> bool {
+        self.webhook.leaderboards.is_empty() || self.webhook.leaderboards.contains(&leaderboard)
+    }
+
+    async fn post(&self, text: &str, blocks: &[Value]) -> Result<()> {
+        for message in slack_messages(text, blocks) {
+            self.client
+                .post(&self.webhook.webhook)
+                .json(&message)
+                .send()
+                .await?
+                .error_for_status()?;
+        }
+        Ok(())
+    }
+}
+
+/// `blocks` in as many messages as Slack needs, the ones after the first
+/// marked as continued.
+fn slack_messages(text: &str, blocks: &[Value]) -> Vec<Value> {
+    blocks
+        .chunks(SLACK_BLOCKS)
+        .enumerate()
+        .map(|(i, blocks)| {
+            let text = if i == 0 {
+                text.to_string()
+            } else {
+                format!("{text} (continued)")
+            };
+            json!({ "text": text, "blocks": blocks })
+        })
+        .collect()
+}
+
+impl Notifier for Slack {
+    fn name(&self) -> &'static str {
+        "Slack"
+    }
+
+    fn notify<'a>(&'a self, diff: &'a Diff) -> Sending<'a> {
+        Box::pin(async move {
+            if !self.webhook.new_stars || !self.wants(diff.leaderboard) {
+                return Ok(());
+            }
+            let title = format!("New stars on {} ({})", diff.leaderboard, diff.year);
+            let mut blocks = vec![header(&title)];
+            blocks.extend(diff.new_stars.iter().map(star_section));
+            self.post(&title, &blocks).await
+        })
+    }
+
+    fn report<'a>(&'a self, report: &'a LeaderboardReport) -> Sending<'a> {
+        Box::pin(async move {
+            if !self.wants(report.leaderboard) {
+                return Ok(());
+            }
+            let title = format!("Leaderboard {} ({})", report.leaderboard, report.year);
+            let mut blocks = vec![header(&title)];
+            let lines: Vec<String> = report
+                .events
+                .iter()
+                .map(|ScoredEvent { event, points, .. }| {
+                    format!(
+                        "`{}` *{}* {} [{points}] ({})",
+                        event.timestamp.format("%H:%M:%S"),
+                        escape(&event.member),
+                        event.star,
+                        event.elapsed_string()
+                    )
+                })
+                .collect();
+            // Section text is limited to 3000 characters.
+            for chunk in lines.chunks(25) {
+                blocks.push(json!({ "type": "section", "text": mrkdwn(chunk.join("\n")) }));
+            }
+            blocks.push(json!({ "type": "divider" }));
+            // Up to ten fields per section: five members with their points.
+            for chunk in report.standings.chunks(5) {
+                let fields: Vec<Value> = chunk
+                    .iter()
+                    .flat_map(|score| {
+                        [
+                            mrkdwn(format!("*{}.* {}", score.rank, escape(&score.member))),
+                            mrkdwn(score.points.to_string()),
+                        ]
+                    })
+                    .collect();
+                blocks.push(json!({ "type": "section", "fields": fields }));
+            }
+            self.post(&title, &blocks).await
+        })
+    }
+}
+
+/// Sends to a Telegram chat through the Bot API.
+pub struct Telegram {
+    pub token: String,
+    pub chat: String,
+    pub client: Client,
+}
+
+/// Telegram rejects messages longer than this.
+const TELEGRAM_LIMIT: usize = 4096;
+
+impl Telegram {
+    async fn send(&self, lines: impl IntoIterator<Item = String>) -> Result<()> {
+        let url = format!("https://api.telegram.org/bot{}/sendMessage", self.token);
+        for text in join_lines(lines, TELEGRAM_LIMIT) {
+            self.client
+                .post(&url)
+                .json(&json!({ "chat_id": self.chat, "text": text }))
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                // The URL holds the bot token.
+                .map_err(reqwest::Error::without_url)?;
+        }
+        Ok(())
+    }
+}
+
+impl Notifier for Telegram {
+    fn name(&self) -> &'static str {
+        "Telegram"
+    }
+
+    fn notify<'a>(&'a self, diff: &'a Diff) -> Sending<'a> {
+        Box::pin(self.send(diff.new_stars.iter().map(message).collect::<Vec<_>>()))
+    }
+
+    /// A summary of the day: how many stars were earned and the standings,
+    /// in as many messages as it takes.
+    fn report<'a>(&'a self, report: &'a LeaderboardReport) -> Sending<'a> {
+        let mut lines = vec![
+            format!("Leaderboard {} ({})", report.leaderboard, report.year),
+            format!(
+                "{} stars today",
+                stars_today(report.year, &report.events, Utc::now())
+            ),
+        ];
+        lines.extend(
+            report
+                .standings
+                .iter()
+                .map(|score| format!("{}. {} {}", score.rank, score.member, score.points)),
+        );
+        Box::pin(self.send(lines))
+    }
+}
+
+/// How many of `events` were earned since the puzzle of the day unlocked,
+/// none once the day after the last puzzle is over.
+fn stars_today(year: i32, events: &[ScoredEvent], now: DateTime<Utc>) -> usize {
+    let Some(unlocked) = (1..=25)
+        .map(|day| unlock(year, day))
+        .filter(|unlocked| *unlocked <= now && now < *unlocked + chrono::Duration::days(1))
+        .max()
+    else {
+        return 0;
+    };
+    events
+        .iter()
+        .filter(|scored| scored.event.timestamp >= unlocked)
+        .count()
+}
+
+/// POSTs each new star as JSON to any URL.
+pub struct Webhook {
+    pub url: String,
+    pub client: Client,
+}
+
+/// What a [`Webhook`] sends for one star.
+#[derive(Serialize)]
+struct StarPayload<'a> {
+    year: i32,
+    leaderboard: i32,
+    #[serde(flatten)]
+    star: &'a NewStar,
+}
+
+impl Notifier for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    fn notify<'a>(&'a self, diff: &'a Diff) -> Sending<'a> {
+        Box::pin(async move {
+            for star in &diff.new_stars {
+                self.client
+                    .post(&self.url)
+                    .json(&StarPayload {
+                        year: diff.year,
+                        leaderboard: diff.leaderboard,
+                        star,
+                    })
+                    .send()
+                    .await?
+                    .error_for_status()?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Runs a shell command for each new star, with the star in `AOC_*`
+/// environment variables and as JSON on stdin.
+pub struct Exec {
+    pub command: String,
+}
+
+impl Exec {
+    /// Runs the command off the runtime's threads, as it may take a while.
+    async fn run(&self, payload: &StarPayload<'_>) -> Result<()> {
+        let command = self.command(payload);
+        let stdin = serde_json::to_vec(payload).unwrap_or_default();
+        let name = self.command.clone();
+        tokio::task::spawn_blocking(move || wait(command, &stdin, &name))
+            .await
+            .map_err(Error::Task)?
+    }
+
+    fn command(&self, payload: &StarPayload) -> Command {
+        let (shell, flag) = if cfg!(windows) {
+            ("cmd", "/C")
+        } else {
+            ("sh", "-c")
+        };
+        let star = payload.star;
+        let mut command = Command::new(shell);
+        command
+            .args([flag, &self.command])
+            .env("AOC_YEAR", payload.year.to_string())
+            .env("AOC_LEADERBOARD", payload.leaderboard.to_string())
+            .env("AOC_TIMESTAMP", star.timestamp.to_rfc3339())
+            .env("AOC_MEMBER", &star.member)
+            .env("AOC_MEMBER_ID", star.member_id.to_string())
+            .env("AOC_DAY", star.day.to_string())
+            .env("AOC_STAR", star.star.to_string())
+            .env(
+                "AOC_ELAPSED_SECONDS",
+                star.elapsed.num_seconds().to_string(),
+            )
+            .env("AOC_POINTS", star.points.to_string())
+            .env("AOC_SCORE", star.score.to_string())
+            .env("AOC_RANK", star.rank.to_string())
+            .stdin(Stdio::piped());
+        command
+    }
+}
+
+fn wait(mut command: Command, stdin: &[u8], name: &str) -> Result<()> {
+    let error = |message: String| Error::Hook {
+        command: name.to_string(),
+        message,
+    };
+    let mut child = command.spawn().map_err(|e| error(e.to_string()))?;
+    if let Some(mut pipe) = child.stdin.take() {
+        // A command that does not read stdin closes it early; that is fine.
+        let _ = pipe.write_all(stdin);
+    }
+    let status = child.wait().map_err(|e| error(e.to_string()))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(error(status.to_string()))
+    }
+}
+
+impl Notifier for Exec {
+    fn name(&self) -> &'static str {
+        "hook"
+    }
+
+    fn notify<'a>(&'a self, diff: &'a Diff) -> Sending<'a> {
+        Box::pin(async move {
+            for star in &diff.new_stars {
+                self.run(&StarPayload {
+                    year: diff.year,
+                    leaderboard: diff.leaderboard,
+                    star,
+                })
+                .await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+/// Pops up a desktop notification for each new star.
+pub struct Desktop;
+
+impl Notifier for Desktop {
+    fn name(&self) -> &'static str {
+        "desktop"
+    }
+
+    fn notify<'a>(&'a self, diff: &'a Diff) -> Sending<'a> {
+        let summary = format!("Advent of Code {}", diff.year);
+        let bodies: Vec<String> = diff.new_stars.iter().map(message).collect();
+        Box::pin(async move {
+            // Showing waits on the desktop's notification service.
+            tokio::task::spawn_blocking(move || {
+                for body in bodies {
+                    notify_rust::Notification::new()
+                        .summary(&summary)
+                        .body(&body)
+                        .show()
+                        .map_err(|e| Error::Desktop(e.to_string()))?;
+                }
+                Ok(())
+            })
+            .await
+            .map_err(Error::Task)?
+        })
+    }
+}
+
+/// The client the config's `[http]` section describes.
+fn client(config: &Config) -> Result<Client> {
+    let mut client = Client::builder()
+        .timeout(Duration::from_secs(config.http.timeout_secs))
+        .connect_timeout(Duration::from_secs(config.http.connect_timeout_secs));
+    if let Some(proxy) = &config.http.proxy {
+        client = client.proxy(Proxy::all(proxy)?);
+    }
+    Ok(client.build()?)
+}
+
+/// Every notifier the config sets up, with a warning in `warnings` for
+/// those that cannot be.
+pub fn notifiers(config: &Config, warnings: &mut Warnings) -> Vec<Box<dyn Notifier>> {
+    let mut notifiers: Vec<Box<dyn Notifier>> = Vec::new();
+    let client = match client(config) {
+        Ok(client) => client,
+        Err(e) => {
+            warnings.push(Category::Notify, format!("not notifying: {e}"));
+            return notifiers;
+        }
+    };
+    if let Some(webhook) = &config.notify.discord_webhook {
+        notifiers.push(Box::new(Discord {
+            webhook: webhook.clone(),
+            client: client.clone(),
+        }));
+    }
+    for webhook in &config.notify.slack {
+        notifiers.push(Box::new(Slack {
+            webhook: webhook.clone(),
+            client: client.clone(),
+        }));
+    }
+    for url in &config.notify.webhooks {
+        notifiers.push(Box::new(Webhook {
+            url: url.clone(),
+            client: client.clone(),
+        }));
+    }
+    if let Some(command) = &config.notify.exec {
+        notifiers.push(Box::new(Exec {
+            command: command.clone(),
+        }));
+    }
+    if config.notify.desktop {
+        notifiers.push(Box::new(Desktop));
+    }
+    if config.notify.telegram {
+        match (
+            secret(config, "telegram_token"),
+            secret(config, "telegram_chat"),
+        ) {
+            (Ok(token), Ok(chat)) => notifiers.push(Box::new(Telegram {
+                token,
+                chat,
+                client,
+            })),
+            (Err(e), _) | (_, Err(e)) => {
+                warnings.push(Category::Notify, format!("not notifying Telegram: {e}"));
+            }
+        }
+    }
+    notifiers
+}
+
+/// [`send_async`] on the shared runtime.
+pub fn send(config: &Config, diffs: &[Diff], warnings: &mut Warnings) {
+    runtime::block_on(send_async(config, diffs, warnings));
+}
+
+/// Announces the new stars in `diffs` everywhere configured. Failures go
+/// in `warnings` so they never cost the report itself.
+pub async fn send_async(config: &Config, diffs: &[Diff], warnings: &mut Warnings) {
+    let notifiers = notifiers(config, warnings);
+    for diff in diffs.iter().filter(|diff| !diff.new_stars.is_empty()) {
+        for notifier in &notifiers {
+            if let Err(e) = notifier.notify(diff).await {
+                warnings.push(
+                    Category::Notify,
+                    format!("failed to notify {}: {e}", notifier.name()),
+                );
+            }
+        }
+    }
+}
+
+/// [`post_reports_async`] on the shared runtime.
+pub fn post_reports(config: &Config, reports: &[LeaderboardReport], warnings: &mut Warnings) {
+    runtime::block_on(post_reports_async(config, reports, warnings));
+}
+
+/// Posts `reports` to every notifier that takes reports, with failures in
+/// `warnings`.
+pub async fn post_reports_async(
+    config: &Config,
+    reports: &[LeaderboardReport],
+    warnings: &mut Warnings,
+) {
+    let notifiers = notifiers(config, warnings);
+    for report in reports {
+        for notifier in &notifiers {
+            if let Err(e) = notifier.report(report).await {
+                warnings.push(
+                    Category::Notify,
+                    format!("failed to post the report to {}: {e}", notifier.name()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{board, member};
+    use crate::{timeline, StarId};
+
+    #[test]
+    fn only_stars_since_the_unlock_are_today() {
+        let (two, three) = (unlock(2024, 2).timestamp(), unlock(2024, 3).timestamp());
+        let aoc = board([member(
+            1,
+            "Alice",
+            &[(2, 1, two + 60), (2, 2, two + 120), (3, 1, three + 60)],
+        )]);
+        let events: Vec<ScoredEvent> = timeline(&aoc)
+            .into_iter()
+            .map(|event| ScoredEvent {
+                event,
+                points: 1,
+                new: false,
+            })
+            .collect();
+        let now = unlock(2024, 3) + chrono::Duration::hours(1);
+        assert_eq!(stars_today(2024, &events, now), 1);
+        assert_eq!(
+            stars_today(2024, &events, now + chrono::Duration::days(30)),
+            0
+        );
+    }
+
+    #[test]
+    fn slack_stars_past_the_block_limit_go_in_another_message() {
+        let blocks: Vec<Value> = (0..120).map(|i| json!({ "n": i })).collect();
+        let messages = slack_messages("New stars", &blocks);
+        let sizes: Vec<usize> = messages
+            .iter()
+            .map(|m| m["blocks"].as_array().unwrap().len())
+            .collect();
+        assert_eq!(sizes, [50, 50, 20]);
+        assert_eq!(messages[2]["text"], "New stars (continued)");
+        assert_eq!(messages[2]["blocks"][19]["n"], 119);
+    }
+
+    #[test]
+    fn slack_member_names_are_escaped() {
+        assert_eq!(
+            escape("<!channel> & <@U123|bob>"),
+            "&lt;!channel&gt; &amp; &lt;@U123|bob&gt;"
+        );
+        let star = NewStar {
+            timestamp: unlock(2024, 7).into(),
+            member: "<!channel> & co".to_string(),
+            member_id: 1,
+            day: 7,
+            star: StarId { day: 7, part: 1 },
+            elapsed: chrono::Duration::minutes(5),
+            points: 10,
+            score: 10,
+            rank: 1,
+        };
+        assert_eq!(
+            star_section(&star)["fields"][0]["text"],
+            "*&lt;!channel&gt; &amp; co*\n07-1"
+        );
+    }
+
+    #[test]
+    fn long_reports_are_split_between_lines() {
+        let lines: Vec<String> = (1..=300)
+            .map(|rank| format!("{rank}. Member number {rank} with a long name 1234"))
+            .collect();
+        let messages = join_lines(lines.clone(), TELEGRAM_LIMIT);
+        assert!(messages.len() > 1);
+        assert!(messages.iter().all(|m| m.len() <= TELEGRAM_LIMIT));
+        assert_eq!(messages.join("\n"), lines.join("\n"));
+    }
+}
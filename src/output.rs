@@ -0,0 +1,65 @@
+//! Reports written to a file with `--output` instead of stdout.
+use crate::error::{Error, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Local};
+use std::path::{Path, PathBuf};
+
+/// Fills in date fields like `%Y-%m-%d` in `path` with `now`, for a file a
+/// day from cron.
+///
+/// # Errors
+///
+/// When `path` has a `%` that is not a date field.
+pub fn dated(path: &Path, now: DateTime<Local>) -> Result<PathBuf> {
+    let template = path.to_string_lossy();
+    let items: Vec<Item> = StrftimeItems::new(&template).collect();
+    if items.contains(&Item::Error) {
+        return Err(Error::Usage {
+            message: format!("'{template}' has an unknown date field, write '%%' for a '%'"),
+        });
+    }
+    Ok(PathBuf::from(
+        now.format_with_items(items.iter()).to_string(),
+    ))
+}
+
+/// Sends stdout to `path` for the rest of the run, creating the file's
+/// directory if needed.
+///
+/// # Errors
+///
+/// When the directory or the file cannot be created.
+pub fn redirect(path: &Path) -> Result<()> {
+    let error = |source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent).map_err(error)?;
+    }
+    let file = std::fs::File::create(path).map_err(error)?;
+    point_stdout_at(&file).map_err(error)
+}
+
+#[cfg(unix)]
+fn point_stdout_at(file: &std::fs::File) -> std::io::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    // SAFETY: stdout becomes a copy of `file`'s descriptor, which stays open
+    // after `file` is closed.
+    if unsafe { libc::dup2(file.as_raw_fd(), libc::STDOUT_FILENO) } < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn point_stdout_at(_file: &std::fs::File) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "--output needs a Unix system, redirect stdout instead",
+    ))
+}
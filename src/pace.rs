@@ -0,0 +1,141 @@
+//! Stars-per-day pace and a projection towards all 50 stars.
+use crate::layout;
+use crate::locale;
+use crate::{display_names, unlock, Aoc};
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use itertools::Itertools;
+use serde::Serialize;
+
+const ALL_STARS: i32 = 50;
+/// Nobody can go faster than both stars of every puzzle as it unlocks.
+const MAX_STARS_PER_DAY: f64 = 2.0;
+
+#[derive(Debug, Serialize)]
+pub struct Pace {
+    pub member: String,
+    pub stars: i32,
+    pub stars_per_day: f64,
+    /// Stars available so far that the member has not collected.
+    pub behind: i32,
+    pub on_track: bool,
+    pub projected_completion: Option<DateTime<Local>>,
+    pub finished: Option<DateTime<Local>>,
+}
+
+/// Computes the pace of every member at `now`, fastest first.
+#[allow(clippy::cast_precision_loss)]
+pub fn pace(aoc: &Aoc, year: i32, now: DateTime<Utc>) -> Vec<Pace> {
+    let first = unlock(year, 1);
+    let end = unlock(year, 25) + Duration::days(1);
+    let now = now.clamp(first, end);
+    let elapsed = now - first;
+    let days = elapsed.num_seconds() as f64 / 86_400.0;
+    let unlocked = if elapsed > Duration::zero() {
+        (elapsed.num_days() + 1).min(25) * 2
+    } else {
+        0
+    };
+
+    let names = display_names(&aoc.members);
+    aoc.members
+        .values()
+        .map(|member| {
+            let stars = member.stars;
+            let stars_per_day = if days > 0.0 {
+                (f64::from(stars) / days).min(MAX_STARS_PER_DAY)
+            } else {
+                0.0
+            };
+            let finished = (stars >= ALL_STARS).then(|| {
+                Local
+                    .timestamp_opt(member.last_star_ts, 0)
+                    .single()
+                    .unwrap()
+            });
+            let projected_completion = if finished.is_some() || stars_per_day == 0.0 {
+                None
+            } else {
+                let remaining = f64::from(ALL_STARS - stars) / stars_per_day;
+                #[allow(clippy::cast_possible_truncation)]
+                let remaining = Duration::seconds((remaining * 86_400.0) as i64);
+                // Day 25 cannot be solved before it unlocks.
+                Some(
+                    (now + remaining)
+                        .max(unlock(year, 25))
+                        .with_timezone(&Local),
+                )
+            };
+            let on_track = finished.is_some()
+                || projected_completion.is_some_and(|p| p <= end.with_timezone(&Local));
+            Pace {
+                member: names[&member.id].clone(),
+                stars,
+                stars_per_day,
+                #[allow(clippy::cast_possible_truncation)]
+                behind: (unlocked as i32 - stars).max(0),
+                on_track,
+                projected_completion,
+                finished,
+            }
+        })
+        .sorted_by(|a, b| {
+            b.stars
+                .cmp(&a.stars)
+                .then_with(|| a.finished.cmp(&b.finished))
+                .then_with(|| a.member.cmp(&b.member))
+        })
+        .collect()
+}
+
+pub fn print(paces: &[Pace]) {
+    println!("\nPace:");
+    for p in paces {
+        let status = if let Some(finished) = p.finished {
+            format!("finished {}", locale::format(&finished, "%B %e %H:%M"))
+        } else if let Some(projected) = p.projected_completion {
+            let behind = if p.behind > 0 {
+                format!("behind by {} stars, ", p.behind)
+            } else {
+                String::new()
+            };
+            let track = if p.on_track { "on track" } else { "off pace" };
+            format!(
+                "{behind}{track}, 50 stars {}",
+                locale::format(&projected, "%B %e")
+            )
+        } else {
+            "no stars yet".to_string()
+        };
+        println!(
+            "  {} {:2}/{ALL_STARS} {:5.2}/day  {status}",
+            layout::name(&p.member),
+            p.stars,
+            p.stars_per_day
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{board, member};
+    use crate::Member;
+
+    #[test]
+    fn fast_starters_cannot_finish_before_day_25() {
+        let aoc = Aoc {
+            event: "2023".into(),
+            ..board([Member {
+                stars: 14,
+                ..member(1, "Alice", &[])
+            }])
+        };
+        let paces = pace(&aoc, 2023, unlock(2023, 1) + Duration::days(3));
+        assert!((paces[0].stars_per_day - MAX_STARS_PER_DAY).abs() < f64::EPSILON);
+        assert_eq!(
+            paces[0].projected_completion,
+            Some(unlock(2023, 25).with_timezone(&Local))
+        );
+        assert!(paces[0].on_track);
+    }
+}
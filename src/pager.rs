@@ -0,0 +1,71 @@
+//! Long reports shown through `$PAGER`, like git does.
+use std::io::{IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+
+/// The pager the rest of the run's output goes to, until dropped.
+pub struct Pager {
+    child: Child,
+}
+
+/// Sends stdout through `$PAGER`, or `less`, when it is a terminal. `less`
+/// gets `-FRX` unless `LESS` says otherwise, so a report that fits on the
+/// screen prints as usual. An empty `PAGER` or `cat` turns paging off.
+pub fn start() -> Option<Pager> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    let command = std::env::var("PAGER").unwrap_or_else(|_| "less".to_string());
+    if command.trim().is_empty() || command.trim() == "cat" {
+        return None;
+    }
+    spawn(&command)
+}
+
+#[cfg(unix)]
+fn spawn(command: &str) -> Option<Pager> {
+    use std::os::fd::AsRawFd;
+
+    let mut pager = Command::new("sh");
+    pager.arg("-c").arg(command).stdin(Stdio::piped());
+    if std::env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+    let mut child = pager.spawn().ok()?;
+    let stdin = child.stdin.take()?;
+    // SAFETY: points stdout at the pager's stdin, which stays open as
+    // stdout after `stdin` is dropped, and only exits from the handler.
+    unsafe {
+        if libc::dup2(stdin.as_raw_fd(), libc::STDOUT_FILENO) < 0 {
+            return None;
+        }
+        libc::signal(libc::SIGPIPE, quit as *const () as libc::sighandler_t);
+    }
+    Some(Pager { child })
+}
+
+/// Ends the run when the pager is quit before the report is over, which is
+/// no failure.
+#[cfg(unix)]
+extern "C" fn quit(_signal: libc::c_int) {
+    // SAFETY: `_exit` is safe to call from a signal handler.
+    unsafe { libc::_exit(0) }
+}
+
+#[cfg(not(unix))]
+fn spawn(_command: &str) -> Option<Pager> {
+    None
+}
+
+impl Drop for Pager {
+    /// Closes stdout, so the pager sees the end of the report, and waits for
+    /// it to be quit.
+    fn drop(&mut self) {
+        let _ = std::io::stdout().flush();
+        #[cfg(unix)]
+        // SAFETY: nothing is written to stdout after the pager is dropped.
+        unsafe {
+            libc::close(libc::STDOUT_FILENO);
+        }
+        let _ = self.child.wait();
+    }
+}
@@ -0,0 +1,262 @@
+//! Monte Carlo projection of the final standings from each member's past
+//! solve times.
+use crate::layout;
+use crate::sample::Rng;
+use crate::scoring::Counting;
+use crate::{display_names, unlock, Aoc, Member, Star};
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Places shown in the text format, the rest are only in JSON.
+const SHOWN_PLACES: usize = 10;
+
+/// One member's chances over all runs.
+#[derive(Debug, Serialize)]
+pub struct Chances {
+    pub member: String,
+    pub member_id: i32,
+    /// Points so far.
+    pub points: usize,
+    /// Average points at the end of the event.
+    pub expected: f64,
+    /// The probability of finishing in each place, first place first.
+    pub places: Vec<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Prediction {
+    pub year: i32,
+    pub leaderboard: i32,
+    pub runs: usize,
+    pub seed: u64,
+    /// Most expected points first.
+    pub chances: Vec<Chances>,
+}
+
+/// What a member's stars so far say about the rest of their event.
+struct Model {
+    /// Seconds from the unlock to part 1.
+    part1: Vec<i64>,
+    /// Seconds from part 1 to part 2.
+    part2: Vec<i64>,
+    /// Share of unlocked days with part 1.
+    part1_rate: f64,
+    /// Share of part 1 stars followed by part 2.
+    part2_rate: f64,
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn model(member: &Member, year: i32, unlocked: usize) -> Model {
+    let mut part1 = Vec::new();
+    let mut part2 = Vec::new();
+    for (day, stars) in member
+        .completion_day_level
+        .iter()
+        .sorted_by_key(|(day, _)| **day)
+    {
+        let Some(first) = stars.get(&1) else {
+            continue;
+        };
+        part1.push(first.get_star_ts - unlock(year, *day).timestamp());
+        if let Some(second) = stars.get(&2) {
+            part2.push(second.get_star_ts - first.get_star_ts);
+        }
+    }
+    Model {
+        part1_rate: if unlocked == 0 {
+            0.0
+        } else {
+            part1.len() as f64 / unlocked as f64
+        },
+        part2_rate: if part1.is_empty() {
+            0.0
+        } else {
+            part2.len() as f64 / part1.len() as f64
+        },
+        part1,
+        part2,
+    }
+}
+
+/// One of `times` picked at random, or `None` when the member misses the
+/// star, which happens with probability `1 - rate`.
+#[allow(clippy::cast_possible_truncation)]
+fn draw(rng: &mut Rng, times: &[i64], rate: f64) -> Option<i64> {
+    if times.is_empty() || rng.next_f64() >= rate {
+        return None;
+    }
+    Some(times[(rng.next_u64() % times.len() as u64) as usize])
+}
+
+/// `aoc` as it could end, with the stars `members` are missing drawn from
+/// their `models`.
+fn finish(
+    aoc: &Aoc,
+    members: &[(&String, &Member)],
+    models: &[Model],
+    rng: &mut Rng,
+    now: i64,
+) -> Aoc {
+    let year = aoc.year();
+    let mut end = aoc.clone();
+    for day in 1..=25 {
+        let unlocked = unlock(year, day).timestamp();
+        for ((key, member), model) in members.iter().zip(models) {
+            let stars = member.completion_day_level.get(&day);
+            let earned = |part| Some(stars?.get(&part)?.get_star_ts);
+            let mut earn = |part, get_star_ts| {
+                if earned(part).is_none() {
+                    end.members
+                        .get_mut(*key)
+                        .unwrap()
+                        .completion_day_level
+                        .entry(day)
+                        .or_default()
+                        .insert(part, Star { get_star_ts });
+                }
+            };
+            let part1 = earned(1).or_else(|| {
+                let time = unlocked + draw(rng, &model.part1, model.part1_rate)?;
+                Some(if time < now {
+                    now + time - unlocked
+                } else {
+                    time
+                })
+            });
+            let Some(part1) = part1 else {
+                continue;
+            };
+            earn(1, part1);
+            let part2 = earned(2).or_else(|| {
+                let gap = draw(rng, &model.part2, model.part2_rate)?;
+                Some(if part1 + gap < now {
+                    now + gap
+                } else {
+                    part1 + gap
+                })
+            });
+            if let Some(part2) = part2 {
+                earn(2, part2);
+            }
+        }
+    }
+    end
+}
+
+/// Finishes the event `runs` times from the stars earned by `now`. Missing
+/// stars are drawn from the member's own part 1 times and part 1 to part 2
+/// gaps, and a drawn time that has already passed is moved to after `now`.
+/// Each finished board is scored by `counting`. The same `seed` gives the
+/// same prediction.
+#[allow(clippy::cast_precision_loss)]
+pub fn predict(
+    aoc: &Aoc,
+    now: DateTime<Utc>,
+    runs: usize,
+    seed: u64,
+    counting: Counting,
+) -> Prediction {
+    let year = aoc.year();
+    let names = display_names(&aoc.members);
+    // In id order, so the same seed draws the same times for the same member.
+    let members: Vec<(&String, &Member)> =
+        aoc.members.iter().sorted_by_key(|(_, m)| m.id).collect();
+    let index: HashMap<i32, usize> = members
+        .iter()
+        .enumerate()
+        .map(|(i, (_, member))| (member.id, i))
+        .collect();
+    let n = members.len();
+    let unlocked = (1..=25).filter(|day| unlock(year, *day) <= now).count();
+    let models: Vec<Model> = members
+        .iter()
+        .map(|(_, member)| model(member, year, unlocked))
+        .collect();
+    let now = now.timestamp();
+    let mut rng = Rng::new(seed);
+    let mut totals = vec![0.0; n];
+    let mut places = vec![vec![0usize; n]; n];
+    for _ in 0..runs {
+        let end = finish(aoc, &members, &models, &mut rng, now);
+        let mut points = vec![0usize; n];
+        for (event, star_points) in counting.scored(&end) {
+            points[index[&event.member_id]] += star_points;
+        }
+        for (i, total) in points.iter().enumerate() {
+            totals[i] += *total as f64;
+            let place = points.iter().filter(|other| *other > total).count();
+            places[i][place] += 1;
+        }
+    }
+    let mut current: HashMap<i32, usize> = HashMap::new();
+    for (event, star_points) in counting.scored(aoc) {
+        *current.entry(event.member_id).or_default() += star_points;
+    }
+    let runs_f = runs.max(1) as f64;
+    let mut chances: Vec<Chances> = members
+        .iter()
+        .enumerate()
+        .map(|(i, (_, member))| Chances {
+            member: names[&member.id].clone(),
+            member_id: member.id,
+            points: current.get(&member.id).copied().unwrap_or(0),
+            expected: totals[i] / runs_f,
+            places: places[i]
+                .iter()
+                .map(|count| *count as f64 / runs_f)
+                .collect(),
+        })
+        .collect();
+    chances.sort_by(|a, b| {
+        b.expected
+            .total_cmp(&a.expected)
+            .then_with(|| a.member.cmp(&b.member))
+    });
+    Prediction {
+        year,
+        leaderboard: aoc.owner_id,
+        runs,
+        seed,
+        chances,
+    }
+}
+
+pub fn print(prediction: &Prediction) {
+    println!(
+        "\nPredicted final standings for leaderboard {} ({}), {} runs, seed {}",
+        prediction.leaderboard, prediction.year, prediction.runs, prediction.seed
+    );
+    let shown = prediction
+        .chances
+        .first()
+        .map_or(0, |chances| chances.places.len().min(SHOWN_PLACES));
+    print!(
+        "  {:width$} {:>6} {:>8}",
+        "",
+        "Points",
+        "Expected",
+        width = layout::name_width()
+    );
+    for place in 1..=shown {
+        print!(" {:>5}", format!("#{place}"));
+    }
+    println!();
+    for chances in &prediction.chances {
+        print!(
+            "  {} {:6} {:8.1}",
+            layout::name(&chances.member),
+            chances.points,
+            chances.expected
+        );
+        for p in chances.places.iter().take(shown) {
+            if *p == 0.0 {
+                print!(" {:>5}", "-");
+            } else {
+                print!(" {:>4.0}%", p * 100.0);
+            }
+        }
+        println!();
+    }
+}
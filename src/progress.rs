@@ -0,0 +1,26 @@
+//! A spinner on stderr while something slow, like a fetch, runs.
+use indicatif::{ProgressBar, ProgressStyle};
+use std::time::Duration;
+use tracing::{info, Level};
+
+/// Spins with `message` and the time so far, like
+/// `⠙ fetching leaderboard 649161 (2024)… 1s`, until the returned bar is
+/// dropped, which clears it. When stderr is not a terminal, or `-q` quiets
+/// progress, nothing is drawn and `message` is only logged.
+pub fn start(message: String) -> ProgressBar {
+    let spinner = if tracing::enabled!(Level::INFO) {
+        ProgressBar::new_spinner()
+    } else {
+        ProgressBar::hidden()
+    };
+    if spinner.is_hidden() {
+        info!("{message}");
+        return spinner;
+    }
+    if let Ok(style) = ProgressStyle::with_template("{spinner} {msg}… {elapsed}") {
+        spinner.set_style(style);
+    }
+    spinner.set_message(message);
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
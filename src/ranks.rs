@@ -0,0 +1,84 @@
+//! Every member's place on the board at the end of each day, replayed from
+//! the timeline.
+use crate::layout;
+use crate::scoring::Counting;
+use crate::{unlock, Aoc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A member's place after each day of the event.
+#[derive(Clone, Debug, Serialize)]
+pub struct Ranks {
+    pub member: String,
+    /// From day 1 on, `None` until the member's first star. Members with the
+    /// same score share a place.
+    pub ranks: Vec<Option<usize>>,
+}
+
+/// The day whose puzzle was the latest to unlock at `timestamp`, so a day
+/// ends when the next puzzle unlocks.
+fn day_at(year: i32, timestamp: i64) -> usize {
+    (2..=25)
+        .take_while(|day| unlock(year, *day).timestamp() <= timestamp)
+        .count()
+        + 1
+}
+
+/// Places after each day up to the latest star by the points of `counting`,
+/// best final place first.
+pub fn ranks(aoc: &Aoc, counting: Counting) -> Vec<Ranks> {
+    let year = aoc.year();
+    let events = counting.scored(aoc);
+    let days = events
+        .iter()
+        .map(|(event, _)| day_at(year, event.timestamp.timestamp()))
+        .max()
+        .unwrap_or(0);
+    let mut totals: HashMap<&str, usize> = HashMap::new();
+    let mut rows: Vec<Ranks> = Vec::new();
+    let mut events = events.iter().peekable();
+    for day in 1..=days {
+        while let Some((event, points)) =
+            events.next_if(|(event, _)| day_at(year, event.timestamp.timestamp()) <= day)
+        {
+            *totals.entry(&event.member).or_default() += points;
+            if !rows.iter().any(|row| *row.member == *event.member) {
+                rows.push(Ranks {
+                    member: event.member.to_string(),
+                    ranks: vec![None; days],
+                });
+            }
+        }
+        for row in &mut rows {
+            let total = totals[row.member.as_str()];
+            row.ranks[day - 1] = Some(1 + totals.values().filter(|t| **t > total).count());
+        }
+    }
+    rows.sort_by(|a, b| {
+        a.ranks
+            .last()
+            .cmp(&b.ranks.last())
+            .then_with(|| a.member.cmp(&b.member))
+    });
+    rows
+}
+
+pub fn print(ranks: &[Ranks]) {
+    println!("\nPlace after each day:");
+    let days = ranks.first().map_or(0, |row| row.ranks.len());
+    print!("  {:width$}", "", width = layout::name_width());
+    for day in 1..=days {
+        print!(" {day:>3}");
+    }
+    println!();
+    for row in ranks {
+        print!("  {}", layout::name(&row.member));
+        for rank in &row.ranks {
+            match rank {
+                Some(rank) => print!(" {rank:>3}"),
+                None => print!("   -"),
+            }
+        }
+        println!();
+    }
+}
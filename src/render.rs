@@ -0,0 +1,635 @@
+//! The report for one leaderboard and the formats it can be written in.
+use crate::achievements::{self, Badge};
+use crate::color;
+use crate::combined::Combined;
+use crate::days::{self, DayStats};
+use crate::export::csv_field;
+use crate::filter::EventFilter;
+use crate::grid::{self, GridRow};
+use crate::layout;
+use crate::locale;
+use crate::medals::{self, MedalCount, Podium};
+use crate::merge::Person;
+use crate::pace::{self, Pace};
+use crate::ranks::{self, Ranks};
+use crate::replay;
+use crate::scoring::{
+    self, break_ties, Counting, Scoring, ScoringSystem, SortKey, SortOrder, TieBreak,
+};
+use crate::streaks::{self, Streak};
+use crate::warnings::Warning;
+use crate::{
+    count_elapsed, display_names, duration_string, time_standings, unlock, Aoc, ElapsedFrom,
+    Report, Score, StarId, TimeScore,
+};
+use chrono::{DateTime, Duration, Local, TimeZone, Utc};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+    /// One JSON object per star and line, for log shippers.
+    Ndjson,
+    Csv,
+    Markdown,
+    Html,
+}
+
+/// Stars by year, member id and star name, like `(2024, 1234, "01-2")`.
+pub type StarKeys = HashSet<(i32, i32, StarId)>;
+
+/// A summary that replaces the timeline in text, and is added to the
+/// report in JSON.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum View {
+    #[default]
+    Timeline,
+    /// Stars per day and a projection towards all 50.
+    Pace,
+    /// Every member's place after each day.
+    Ranks,
+    /// Solve counts and times per day.
+    Days,
+    /// Which stars every member has, day by day.
+    Grid,
+    /// Gold, silver and bronze counts for the first three on every star.
+    Medals,
+    /// Badges for memorable moments like the first star of the event.
+    Badges,
+}
+
+/// What goes into a report.
+#[derive(Default)]
+pub struct Options<'a> {
+    /// Every event rather than only today's.
+    pub all: bool,
+    /// Only events, podiums and day stats of these days, rather than today's
+    /// events, unless empty.
+    pub days: &'a [u32],
+    /// Only events from this instant on, rather than today's.
+    pub since: Option<DateTime<Utc>>,
+    /// Only events up to this instant, rather than today's.
+    pub until: Option<DateTime<Utc>>,
+    /// Only events of this many calendar days up to today, rather than
+    /// today's alone.
+    pub last: Option<u32>,
+    /// Only events for these member ids.
+    pub members: Option<&'a HashSet<i32>>,
+    /// Only this many members of the leaderboard, and those in `members`.
+    pub top: Option<usize>,
+    /// Rank by stars and solve time on boards smaller than this.
+    pub min_members: Option<usize>,
+    /// What to show in place of the timeline in the text format.
+    pub view: View,
+    /// How to rank the leaderboard.
+    pub scoring: ScoringSystem,
+    /// Days that score no points but still show in the timeline.
+    pub exclude_days: &'a [u32],
+    /// How to order members on the same score.
+    pub tie_breaks: &'a [TieBreak],
+    /// What to list the leaderboard by, and which way, rather than by place.
+    pub sort: Option<(SortKey, SortOrder)>,
+    /// Add each score as a percent of the most points possible.
+    pub normalize: bool,
+    /// Add the local score Advent of Code reports next to each score.
+    pub local_score: bool,
+    /// What elapsed times count from.
+    pub elapsed_from: ElapsedFrom,
+    /// Report as of this instant rather than now.
+    pub as_of: Option<DateTime<Utc>>,
+    /// Stars to mark as new.
+    pub new: Option<&'a StarKeys>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScoredEvent {
+    #[serde(flatten)]
+    pub event: Report,
+    pub points: usize,
+    /// Earned since the previous refresh when watching.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub new: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LeaderboardReport {
+    pub year: i32,
+    pub leaderboard: i32,
+    pub events: Vec<ScoredEvent>,
+    pub scoring: ScoringSystem,
+    pub standings: Vec<Score>,
+    /// How many members `--top` left out of `standings`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub more: Option<usize>,
+    /// Set when the board has fewer members than this.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_members: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_standings: Option<Vec<TimeScore>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pace: Option<Vec<Pace>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ranks: Option<Vec<Ranks>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<Vec<DayStats>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grid: Option<Vec<GridRow>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub medals: Option<Vec<MedalCount>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub badges: Option<Vec<Badge>>,
+    /// The first three on part 2 of every day.
+    pub podiums: Vec<Podium>,
+    /// Runs of days with both stars on the day of release.
+    pub streaks: Vec<Streak>,
+}
+
+/// Everything a run produced, for the machine-readable formats.
+#[derive(Debug, Serialize)]
+pub struct Document {
+    pub leaderboards: Vec<LeaderboardReport>,
+    /// Each person once across the boards, with `--merge`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merged: Option<Vec<Person>>,
+    /// Every board in one timeline, with `--combined`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub combined: Option<Combined>,
+    pub warnings: Vec<Warning>,
+}
+
+pub fn build(aoc: &Aoc, options: &Options) -> LeaderboardReport {
+    let year = aoc.year();
+    let now = options.as_of.unwrap_or_else(Utc::now);
+    let filters = event_filters(options, now);
+    let scoring = options.scoring.scoring();
+    let counting = Counting {
+        scoring,
+        exclude_days: options.exclude_days,
+    };
+    let counted = if options.exclude_days.is_empty() {
+        Cow::Borrowed(aoc)
+    } else {
+        Cow::Owned(replay::without_days(aoc, options.exclude_days))
+    };
+    let counted_scores = scoring.scored(&counted);
+    let mut scored = counting.scored(aoc);
+    count_elapsed(
+        scored.iter_mut().map(|(event, _)| event),
+        year,
+        options.elapsed_from,
+    );
+    let mut standings = scoring.standings(&counted, &counted_scores);
+    break_ties(
+        &mut standings,
+        &counted,
+        &counted_scores,
+        options.tie_breaks,
+    );
+    if options.normalize {
+        normalize(&mut standings, aoc, scoring, options, now);
+    }
+    if options.local_score {
+        let names = display_names(&aoc.members);
+        let local_scores: HashMap<&str, i32> = aoc
+            .members
+            .values()
+            .map(|member| (names[&member.id].as_str(), member.local_score))
+            .collect();
+        for score in &mut standings {
+            score.local_score = local_scores.get(score.member.as_str()).copied();
+        }
+    }
+    if let Some((key, order)) = options.sort {
+        scoring::sort(&mut standings, aoc, key, order);
+    }
+    let more = options
+        .top
+        .map(|top| truncate(&mut standings, top, aoc, options.members));
+    let min_members = options.min_members.filter(|min| aoc.members.len() < *min);
+    LeaderboardReport {
+        year: aoc.year(),
+        leaderboard: aoc.owner_id,
+        scoring: options.scoring,
+        standings,
+        more,
+        time_standings: min_members.map(|_| time_standings(&counted_scores)),
+        min_members,
+        pace: (options.view == View::Pace).then(|| pace::pace(aoc, aoc.year(), now)),
+        ranks: (options.view == View::Ranks).then(|| ranks::ranks(aoc, counting)),
+        days: (options.view == View::Days).then(|| {
+            days::days(aoc)
+                .into_iter()
+                .filter(|stats| on_day(options, stats.day))
+                .collect()
+        }),
+        grid: (options.view == View::Grid).then(|| grid::grid(aoc)),
+        medals: (options.view == View::Medals).then(|| medals::medals(&medals::podiums(aoc))),
+        badges: (options.view == View::Badges).then(|| achievements::badges(aoc, now, counting)),
+        streaks: streaks::streaks(aoc, now),
+        podiums: medals::podiums(aoc)
+            .into_iter()
+            .filter(|podium| podium.part == 2 && on_day(options, podium.day))
+            .collect(),
+        events: scored
+            .into_iter()
+            .filter(|(event, _)| filters.iter().all(|filter| filter.keeps(event)))
+            .map(|(event, points)| ScoredEvent {
+                new: options
+                    .new
+                    .is_some_and(|new| new.contains(&(year, event.member_id, event.star))),
+                event,
+                points,
+            })
+            .collect(),
+    }
+}
+
+/// The filters for the timeline: today's events unless `--all`, a day or a
+/// time range or `--last` asks for others, and any member or day limits on top.
+fn event_filters<'a>(options: &Options<'a>, now: DateTime<Utc>) -> Vec<EventFilter<'a>> {
+    let mut filters = Vec::new();
+    let today = now.with_timezone(&Local).date_naive();
+    if !options.all
+        && options.days.is_empty()
+        && options.since.is_none()
+        && options.until.is_none()
+        && options.last.is_none()
+    {
+        filters.push(EventFilter::On(today));
+    }
+    if let Some(last) = options.last {
+        let first = today - Duration::days(i64::from(last) - 1);
+        filters.extend(
+            Local
+                .from_local_datetime(&first.and_hms_opt(0, 0, 0).unwrap())
+                .earliest()
+                .map(|start| EventFilter::Since(start.with_timezone(&Utc))),
+        );
+    }
+    filters.extend(options.members.map(EventFilter::Members));
+    if !options.days.is_empty() {
+        filters.push(EventFilter::Days(options.days));
+    }
+    filters.extend(options.since.map(EventFilter::Since));
+    filters.extend(options.until.map(EventFilter::Until));
+    filters
+}
+
+/// Keeps the first `top` of `standings` and any of `members`, returning how
+/// many were left out.
+fn truncate(
+    standings: &mut Vec<Score>,
+    top: usize,
+    aoc: &Aoc,
+    members: Option<&HashSet<i32>>,
+) -> usize {
+    let names = display_names(&aoc.members);
+    let kept: HashSet<&str> = members
+        .into_iter()
+        .flatten()
+        .filter_map(|id| names.get(id).map(String::as_str))
+        .collect();
+    let total = standings.len();
+    let mut place = 0;
+    standings.retain(|score| {
+        place += 1;
+        place <= top || kept.contains(score.member.as_str())
+    });
+    total - standings.len()
+}
+
+/// Whether `day` is one of the days `options` is limited to.
+fn on_day(options: &Options, day: u32) -> bool {
+    options.days.is_empty() || options.days.contains(&day)
+}
+
+/// Sets every score's percent of the most points possible from the stars
+/// unlocked by `now`, leaving out excluded days.
+#[allow(clippy::cast_precision_loss)]
+fn normalize(
+    standings: &mut [Score],
+    aoc: &Aoc,
+    scoring: &dyn Scoring,
+    options: &Options,
+    now: DateTime<Utc>,
+) {
+    let days = (1..=25)
+        .filter(|day| unlock(aoc.year(), *day) <= now && !options.exclude_days.contains(day))
+        .count();
+    let Some(max) = scoring.max_points(aoc, 2 * days).filter(|max| *max > 0) else {
+        return;
+    };
+    for score in standings {
+        score.percent = Some(100.0 * score.points as f64 / max as f64);
+    }
+}
+
+/// `Trent 345 (61.6%)`-style percent after a score, when there is one.
+fn percent(score: &Score) -> String {
+    score
+        .percent
+        .map(|percent| format!(" ({percent:.1}%)"))
+        .unwrap_or_default()
+}
+
+/// How far the local score Advent of Code reports is from `score`, like
+/// `+12`, when it was asked for.
+pub(crate) fn local_difference(score: &Score) -> Option<String> {
+    let local = i64::from(score.local_score?);
+    Some(format!(
+        "{:+}",
+        local - i64::try_from(score.points).unwrap()
+    ))
+}
+
+/// The place, name and time from the unlock of the first three on part 2 of
+/// `day`.
+pub(crate) fn podium(report: &LeaderboardReport, day: u32) -> Vec<(usize, &str, Duration)> {
+    let Some(podium) = report.podiums.iter().find(|podium| podium.day == day) else {
+        return Vec::new();
+    };
+    let unlocked = unlock(report.year, day);
+    podium
+        .places
+        .iter()
+        .map(|(place, event)| {
+            (
+                *place,
+                &*event.member,
+                event.timestamp.with_timezone(&Utc) - unlocked,
+            )
+        })
+        .collect()
+}
+
+/// `Podium: 1. Trent 32:17, 2. Carol 1:10:16, ...` for the text format.
+fn podium_line(report: &LeaderboardReport, day: u32) -> Option<String> {
+    let places = podium(report, day);
+    (!places.is_empty()).then(|| {
+        let places: Vec<String> = places
+            .iter()
+            .map(|(place, member, time)| format!("{place}. {member} {}", duration_string(*time)))
+            .collect();
+        format!("  Podium {day:02}-2: {}", places.join(", "))
+    })
+}
+
+pub fn text(report: &LeaderboardReport) {
+    let mut day = String::new();
+    let mut day_number = None;
+    let separator = if layout::stacked() { "\n    " } else { "\t" };
+    for ScoredEvent { event, points, new } in &report.events {
+        let event_day = locale::format(&event.timestamp, "%B %e");
+        if event_day != day {
+            if let Some(line) = day_number.and_then(|d| podium_line(report, d)) {
+                println!("{line}");
+            }
+            println!("\n{}", color::heading(&event_day));
+            day = event_day;
+            day_number = None;
+        }
+        // The newest puzzle of the day, as late solves of older ones come
+        // first.
+        day_number = day_number.max(Some(event.day()));
+        println!(
+            "{} {} {}{separator}{} [{}] ({}){}",
+            if *new { '*' } else { ' ' },
+            event.timestamp.time(),
+            color::member(layout::name(&event.member), &event.member),
+            color::star(event.star, event.part()),
+            points,
+            event.elapsed_string(),
+            delta(event)
+        );
+    }
+    if let Some(line) = day_number.and_then(|d| podium_line(report, d)) {
+        println!("{line}");
+    }
+    println!("\n{}", color::heading("Leaderboard:"));
+    if let (Some(min), Some(time_standings)) = (report.min_members, &report.time_standings) {
+        println!("  (fewer than {min} members, ranking by stars and total solve time)");
+        for score in time_standings {
+            println!(
+                "  {} {:2}* {}",
+                layout::name(&score.member),
+                score.stars,
+                duration_string(score.time)
+            );
+        }
+    } else {
+        for score in &report.standings {
+            print!(
+                "  {:>3}. {} {}{}",
+                score.rank,
+                color::member(layout::name(&score.member), &score.member),
+                if score.rank <= 3 {
+                    color::highlight(score.points)
+                } else {
+                    score.points.to_string()
+                },
+                percent(score)
+            );
+            match (score.local_score, local_difference(score)) {
+                (Some(local), Some(difference)) => println!("  AoC {local} ({difference})"),
+                _ => println!(),
+            }
+        }
+        if let Some(more) = report.more.filter(|more| *more > 0) {
+            println!("  … and {more} more");
+        }
+    }
+    if !report.streaks.is_empty() {
+        println!(
+            "\nStreaks (both stars on release day): {}",
+            streaks::summary(&report.streaks, str::to_string)
+        );
+    }
+}
+
+/// The time since part 1 after a part 2 star, like ` +12:34`.
+fn delta(event: &Report) -> String {
+    event
+        .delta
+        .map(|delta| format!(" +{}", duration_string(delta)))
+        .unwrap_or_default()
+}
+
+/// Escapes the characters that would break a Markdown table cell.
+pub(crate) fn cell(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// GitHub-flavored Markdown: a table per day, then the leaderboard.
+pub fn markdown(report: &LeaderboardReport) -> String {
+    let mut md = format!("## {} leaderboard {}\n", report.year, report.leaderboard);
+    let footer = |md: &mut String, day: Option<u32>| {
+        let Some(day) = day else {
+            return;
+        };
+        let places = podium(report, day);
+        if !places.is_empty() {
+            let places: Vec<String> = places
+                .iter()
+                .map(|(place, member, time)| {
+                    format!("{place}. {} {}", cell(member), duration_string(*time))
+                })
+                .collect();
+            write!(md, "\n**Podium {day:02}-2:** {}\n", places.join(", ")).unwrap();
+        }
+    };
+    let mut day = String::new();
+    let mut day_number = None;
+    for ScoredEvent { event, points, .. } in &report.events {
+        let event_day = locale::format(&event.timestamp, "%B %-d");
+        if event_day != day {
+            footer(&mut md, day_number);
+            day_number = None;
+            write!(
+                md,
+                "\n### {event_day}\n\n| Time | Member | Star | Points | Elapsed | Since part 1 |\n\
+                 |---|---|---|--:|--:|--:|\n"
+            )
+            .unwrap();
+            day = event_day;
+        }
+        day_number = day_number.max(Some(event.day()));
+        writeln!(
+            md,
+            "| {} | {} | {} | {points} | {} | {} |",
+            event.timestamp.time(),
+            cell(&event.member),
+            event.star,
+            event.elapsed_string(),
+            event.delta.map(duration_string).unwrap_or_default()
+        )
+        .unwrap();
+    }
+    footer(&mut md, day_number);
+    markdown_standings(&mut md, report);
+    if !report.streaks.is_empty() {
+        writeln!(
+            md,
+            "\n_Streaks (both stars on release day): {}_",
+            streaks::summary(&report.streaks, cell)
+        )
+        .unwrap();
+    }
+    md
+}
+
+/// The leaderboard table of the Markdown report.
+fn markdown_standings(md: &mut String, report: &LeaderboardReport) {
+    md.push_str("\n### Leaderboard\n\n");
+    if let (Some(min), Some(time_standings)) = (report.min_members, &report.time_standings) {
+        writeln!(
+            md,
+            "_Fewer than {min} members, ranking by stars and total solve time._\n"
+        )
+        .unwrap();
+        md.push_str("| # | Member | Stars | Solve time |\n|--:|---|--:|--:|\n");
+        for (rank, score) in time_standings.iter().enumerate() {
+            writeln!(
+                md,
+                "| {} | {} | {} | {} |",
+                rank + 1,
+                cell(&score.member),
+                score.stars,
+                duration_string(score.time)
+            )
+            .unwrap();
+        }
+    } else {
+        if report.standings.iter().any(|s| s.local_score.is_some()) {
+            writeln!(
+                md,
+                "| # | Member | {} | AoC | Difference |\n|--:|---|--:|--:|--:|",
+                report.scoring.label()
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                md,
+                "| # | Member | {} |\n|--:|---|--:|",
+                report.scoring.label()
+            )
+            .unwrap();
+        }
+        for score in &report.standings {
+            writeln!(
+                md,
+                "| {} | {} | {}{} |{}",
+                score.rank,
+                cell(&score.member),
+                score.points,
+                percent(score),
+                match (score.local_score, local_difference(score)) {
+                    (Some(local), Some(difference)) => format!(" {local} | {difference} |"),
+                    _ => String::new(),
+                }
+            )
+            .unwrap();
+        }
+        if let Some(more) = report.more.filter(|more| *more > 0) {
+            writeln!(md, "\n_… and {more} more._").unwrap();
+        }
+    }
+}
+
+pub fn json(document: &Document) {
+    println!("{}", serde_json::to_string_pretty(document).unwrap());
+}
+
+/// A star of the NDJSON stream, with the board it is on.
+#[derive(Serialize)]
+struct StreamedEvent<'a> {
+    year: i32,
+    leaderboard: i32,
+    #[serde(flatten)]
+    event: &'a ScoredEvent,
+}
+
+/// Prints the stars of `report` as NDJSON, or only the new ones with
+/// `new_only`, a line each as soon as the board is built.
+pub fn ndjson(report: &LeaderboardReport, new_only: bool) {
+    for event in report.events.iter().filter(|event| !new_only || event.new) {
+        let line = StreamedEvent {
+            year: report.year,
+            leaderboard: report.leaderboard,
+            event,
+        };
+        println!("{}", serde_json::to_string(&line).unwrap());
+    }
+}
+
+/// One row per star, for spreadsheets.
+pub fn csv(reports: &[LeaderboardReport]) -> String {
+    let mut csv = String::from(
+        "leaderboard,timestamp,member,day,star,elapsed_seconds,points,delta_seconds\n",
+    );
+    for report in reports {
+        for ScoredEvent { event, points, .. } in &report.events {
+            writeln!(
+                csv,
+                "{},{},{},{},{},{},{points},{}",
+                report.leaderboard,
+                event.timestamp.to_rfc3339(),
+                csv_field(&event.member),
+                event.day(),
+                event.star,
+                event.elapsed.num_seconds(),
+                event
+                    .delta
+                    .map(|delta| delta.num_seconds().to_string())
+                    .unwrap_or_default(),
+            )
+            .unwrap();
+        }
+    }
+    csv
+}
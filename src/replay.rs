@@ -0,0 +1,67 @@
+//! Boards as they stood at an earlier instant.
+use crate::Aoc;
+use chrono::{DateTime, Utc};
+
+/// Drops every star earned after `at` and recomputes each member's star
+/// count and last star to match. The local score stays the one Advent of
+/// Code reported.
+pub fn as_of(aoc: &mut Aoc, at: DateTime<Utc>) {
+    let cutoff = at.timestamp();
+    for member in aoc.members.values_mut() {
+        for stars in member.completion_day_level.values_mut() {
+            stars.retain(|_, star| star.get_star_ts <= cutoff);
+        }
+    }
+    recount(aoc);
+}
+
+/// A copy of `aoc` without the stars of `days`, with star counts recomputed
+/// as if those days never happened.
+pub fn without_days(aoc: &Aoc, days: &[u32]) -> Aoc {
+    let mut aoc = aoc.clone();
+    for member in aoc.members.values_mut() {
+        member
+            .completion_day_level
+            .retain(|day, _| !days.contains(day));
+    }
+    recount(&mut aoc);
+    aoc
+}
+
+/// Recomputes each member's star count and last star from their stars. The
+/// local score is left alone: it is Advent of Code's, and `--local-score` and
+/// `--scoring official` show it as reported.
+pub(crate) fn recount(aoc: &mut Aoc) {
+    for member in aoc.members.values_mut() {
+        member
+            .completion_day_level
+            .retain(|_, stars| !stars.is_empty());
+        let times = member
+            .completion_day_level
+            .values()
+            .flat_map(|stars| stars.values().map(|star| star.get_star_ts));
+        member.stars = i32::try_from(times.clone().count()).unwrap();
+        member.last_star_ts = times.max().unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{board, member};
+    use crate::{unlock, Member};
+
+    #[test]
+    fn replaying_keeps_the_official_local_score() {
+        let start = unlock(2024, 1).timestamp();
+        let mut aoc = board([Member {
+            local_score: 7,
+            ..member(1, "Alice", &[(1, 1, start + 60), (1, 2, start + 600)])
+        }]);
+        as_of(&mut aoc, unlock(2024, 1) + chrono::Duration::minutes(5));
+        let alice = &aoc.members["1"];
+        assert_eq!((alice.stars, alice.last_star_ts), (1, start + 60));
+        assert_eq!(alice.local_score, 7);
+        assert_eq!(without_days(&aoc, &[1]).members["1"].local_score, 7);
+    }
+}
@@ -0,0 +1,24 @@
+//! The one tokio runtime that networking runs on, for blocking callers like
+//! the command line to wait on.
+use std::future::Future;
+use std::sync::OnceLock;
+use tokio::runtime::{Builder, Runtime};
+
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+
+/// The runtime, started on first use.
+pub fn runtime() -> &'static Runtime {
+    RUNTIME.get_or_init(|| {
+        Builder::new_multi_thread()
+            .enable_all()
+            .thread_name("aoc-timeline")
+            .build()
+            .expect("failed to start the tokio runtime")
+    })
+}
+
+/// Runs `future` to completion on [`runtime`], blocking the caller. Not to
+/// be called from inside the runtime.
+pub fn block_on<F: Future>(future: F) -> F::Output {
+    runtime().block_on(future)
+}
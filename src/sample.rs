@@ -0,0 +1,124 @@
+//! Deterministic fake leaderboard for demos, first runs and tests.
+use crate::{unlock, Aoc, Member, Star};
+use std::collections::HashMap;
+
+pub const SEED: u64 = 2015;
+
+const NAMES: [Option<&str>; 8] = [
+    Some("Alice"),
+    Some("Bob"),
+    Some("Carol"),
+    Some("Dave"),
+    Some("Eve"),
+    None,
+    Some("Mallory"),
+    Some("Trent"),
+];
+
+/// Small splitmix64 generator, good enough for reproducible fake data.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Rng(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in [0, 1).
+    #[allow(clippy::cast_precision_loss)]
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Builds a finished leaderboard for `year` with a handful of members of
+/// varying skill, stamina and sleeping habits.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+pub fn leaderboard(year: i32, seed: u64) -> Aoc {
+    let mut rng = Rng::new(seed);
+    let mut members = HashMap::new();
+    for (idx, name) in NAMES.iter().enumerate() {
+        let id = 100_001 + idx as i32 * 7_919;
+        // Seconds-per-difficulty multiplier, hours slept in and chance to give up per day.
+        let skill = 300.0 + rng.next_f64() * 1_500.0;
+        let sleep_in = if rng.next_f64() < 0.4 {
+            rng.next_f64() * 8.0
+        } else {
+            0.0
+        };
+        let stamina = rng.next_f64() * 0.08;
+
+        let mut completion_day_level = HashMap::new();
+        let mut star_count = 0;
+        let mut last_star_ts = 0;
+        for day in 1..=25 {
+            if rng.next_f64() < stamina * f64::from(day) / 10.0 {
+                break;
+            }
+            let unlocked = unlock(year, day).timestamp();
+            let difficulty = 1.0 + f64::from(day) / 5.0 + rng.next_f64() * 2.0;
+            let start = unlocked + (sleep_in * 3_600.0 * rng.next_f64()) as i64;
+            let part1 = start + (skill * difficulty * (0.5 + rng.next_f64())) as i64;
+            let mut solved = HashMap::from([(1, Star { get_star_ts: part1 })]);
+            star_count += 1;
+            last_star_ts = part1;
+            let earned_all = star_count == 49;
+            if (day < 25 && rng.next_f64() < 0.9) || (day == 25 && earned_all) {
+                let part2 = part1 + (skill * difficulty * rng.next_f64() * 1.5) as i64;
+                solved.insert(2, Star { get_star_ts: part2 });
+                star_count += 1;
+                last_star_ts = part2;
+            }
+            completion_day_level.insert(day, solved);
+        }
+        members.insert(
+            id.to_string(),
+            Member {
+                global_score: 0,
+                name: name.map(str::to_string),
+                stars: star_count,
+                id,
+                last_star_ts,
+                local_score: 0,
+                completion_day_level,
+            },
+        );
+    }
+    assign_local_scores(&mut members);
+    Aoc {
+        event: year.to_string(),
+        owner_id: 100_001,
+        members,
+    }
+}
+
+/// Fills in `local_score` the way adventofcode.com does: for each star the
+/// first solver gets one point per member, the next one point less and so on.
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+fn assign_local_scores(members: &mut HashMap<String, Member>) {
+    let n = members.len() as i32;
+    let mut solves: HashMap<(u32, u32), Vec<(i64, String)>> = HashMap::new();
+    for (key, member) in members.iter() {
+        for (day, parts) in &member.completion_day_level {
+            for (part, star) in parts {
+                solves
+                    .entry((*day, *part))
+                    .or_default()
+                    .push((star.get_star_ts, key.clone()));
+            }
+        }
+    }
+    for mut solvers in solves.into_values() {
+        solvers.sort();
+        for (rank, (_, key)) in solvers.iter().enumerate() {
+            members.get_mut(key).unwrap().local_score += n - rank as i32;
+        }
+    }
+}
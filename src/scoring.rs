@@ -0,0 +1,436 @@
+//! The house rules for turning stars into points and points into standings.
+use crate::{
+    automatic, display_names, elo, rank, replay, scored, standings, unlock, Aoc, Report, Score,
+};
+use chrono::Utc;
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashMap;
+
+/// A way to score a leaderboard. Implement it and add a [`ScoringSystem`]
+/// variant to offer a new one on the command line.
+pub trait Scoring: Sync {
+    /// Every event of the timeline with the points it earned.
+    fn scored(&self, aoc: &Aoc) -> Vec<(Report, usize)>;
+
+    /// The leaderboard, best first. Adds up the points of `scored` by default.
+    fn standings(&self, _aoc: &Aoc, scored: &[(Report, usize)]) -> Vec<Score> {
+        standings(scored)
+    }
+
+    /// The most points one member can have from `stars` stars on `aoc`, or
+    /// `None` when scores have no ceiling. One point per member per star by
+    /// default.
+    fn max_points(&self, aoc: &Aoc, stars: usize) -> Option<usize> {
+        Some(aoc.members.len() * stars)
+    }
+}
+
+/// One point per member for the first to get a star, one less for the next
+/// and so on.
+pub struct Positional;
+
+impl Scoring for Positional {
+    fn scored(&self, aoc: &Aoc) -> Vec<(Report, usize)> {
+        scored(aoc)
+    }
+}
+
+/// Points per star like [`Positional`], ranked by the `local_score` Advent of
+/// Code reports for each member, which counts day 25's free star too.
+pub struct Official;
+
+impl Scoring for Official {
+    fn scored(&self, aoc: &Aoc) -> Vec<(Report, usize)> {
+        scored(aoc)
+    }
+
+    fn standings(&self, aoc: &Aoc, _scored: &[(Report, usize)]) -> Vec<Score> {
+        let names = display_names(&aoc.members);
+        let mut standings: Vec<Score> = aoc
+            .members
+            .values()
+            .map(|member| Score {
+                rank: 0,
+                member: names[&member.id].clone(),
+                points: usize::try_from(member.local_score).unwrap_or(0),
+                percent: None,
+                local_score: None,
+            })
+            .sorted_by(|a, b| {
+                b.points
+                    .cmp(&a.points)
+                    .then_with(|| a.member.cmp(&b.member))
+            })
+            .collect();
+        rank(&mut standings, |a, b| a.points == b.points);
+        standings
+    }
+}
+
+/// One point per star, however late, but none for day 25's free one.
+pub struct StarsOnly;
+
+impl Scoring for StarsOnly {
+    fn scored(&self, aoc: &Aoc) -> Vec<(Report, usize)> {
+        scored(aoc)
+            .into_iter()
+            .map(|(event, _)| {
+                let points = usize::from(!automatic(event.day(), event.part()));
+                (event, points)
+            })
+            .collect()
+    }
+
+    fn max_points(&self, _aoc: &Aoc, stars: usize) -> Option<usize> {
+        Some(stars)
+    }
+}
+
+/// The minutes left in the first day after the puzzle unlocked, so faster
+/// stars score more whoever else is on the board. Stars after the first day
+/// score one point, and day 25's free star none.
+pub struct TimeBased;
+
+impl Scoring for TimeBased {
+    fn scored(&self, aoc: &Aoc) -> Vec<(Report, usize)> {
+        let year = aoc.year();
+        scored(aoc)
+            .into_iter()
+            .map(|(event, _)| {
+                if automatic(event.day(), event.part()) {
+                    return (event, 0);
+                }
+                let taken = event.timestamp.with_timezone(&Utc) - unlock(year, event.day());
+                let left = 24 * 60 - taken.num_minutes();
+                (event, usize::try_from(left).unwrap_or(0).max(1))
+            })
+            .collect()
+    }
+
+    fn max_points(&self, _aoc: &Aoc, stars: usize) -> Option<usize> {
+        Some(24 * 60 * stars)
+    }
+}
+
+/// Points per star like [`Positional`], ranked by [`elo::ratings`].
+pub struct Elo;
+
+impl Scoring for Elo {
+    fn scored(&self, aoc: &Aoc) -> Vec<(Report, usize)> {
+        scored(aoc)
+    }
+
+    fn standings(&self, aoc: &Aoc, _scored: &[(Report, usize)]) -> Vec<Score> {
+        elo::ratings(aoc)
+    }
+
+    fn max_points(&self, _aoc: &Aoc, _stars: usize) -> Option<usize> {
+        None
+    }
+}
+
+/// The scoring picked with `--scoring`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoringSystem {
+    /// One point per member for the first to get a star, one less for the
+    /// next and so on.
+    #[default]
+    Points,
+    /// Ranked by the local score Advent of Code reports.
+    Official,
+    /// One point per star.
+    Stars,
+    /// Minutes left in the first day when the star was earned.
+    Time,
+    /// An Elo rating from playing every day as a round robin.
+    Elo,
+}
+
+impl ScoringSystem {
+    pub fn scoring(self) -> &'static dyn Scoring {
+        match self {
+            ScoringSystem::Points => &Positional,
+            ScoringSystem::Official => &Official,
+            ScoringSystem::Stars => &StarsOnly,
+            ScoringSystem::Time => &TimeBased,
+            ScoringSystem::Elo => &Elo,
+        }
+    }
+
+    /// The heading of the score column.
+    pub fn label(self) -> &'static str {
+        match self {
+            ScoringSystem::Stars => "Stars",
+            ScoringSystem::Elo => "Rating",
+            _ => "Points",
+        }
+    }
+}
+
+/// Which stars count for how much: the picked scoring without the excluded
+/// days, for everything that shows points next to the report.
+#[derive(Clone, Copy)]
+pub struct Counting<'a> {
+    pub scoring: &'a dyn Scoring,
+    /// Days whose stars score nothing.
+    pub exclude_days: &'a [u32],
+}
+
+impl Default for Counting<'_> {
+    /// Positional points for every day.
+    fn default() -> Self {
+        Counting {
+            scoring: &Positional,
+            exclude_days: &[],
+        }
+    }
+}
+
+impl Counting<'_> {
+    /// [`Scoring::scored`], with no points for stars on excluded days.
+    pub fn scored(&self, aoc: &Aoc) -> Vec<(Report, usize)> {
+        let mut scored = self.scoring.scored(aoc);
+        for (event, points) in &mut scored {
+            if self.exclude_days.contains(&event.day()) {
+                *points = 0;
+            }
+        }
+        scored
+    }
+
+    /// [`Scoring::standings`] as if the excluded days never happened.
+    pub fn standings(&self, aoc: &Aoc, scored: &[(Report, usize)]) -> Vec<Score> {
+        if self.exclude_days.is_empty() {
+            self.scoring.standings(aoc, scored)
+        } else {
+            let counted = replay::without_days(aoc, self.exclude_days);
+            self.scoring.standings(&counted, scored)
+        }
+    }
+}
+
+/// A way to order members on the same score, picked with `--tie-break`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum TieBreak {
+    /// More stars first.
+    Stars,
+    /// Earlier last star first, as Advent of Code does.
+    LastStar,
+    /// Whoever reached their score first.
+    FirstToScore,
+}
+
+/// What to list the leaderboard by, picked with `--sort`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum SortKey {
+    /// The score of the scoring system, highest first.
+    #[default]
+    Score,
+    /// Stars, most first.
+    Stars,
+    /// The local score Advent of Code reports, highest first.
+    LocalScore,
+    /// When the last star was earned, earliest first.
+    LastStar,
+    /// Name, alphabetically.
+    Name,
+}
+
+/// Which way `--sort-order` lists the leaderboard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortKey {
+    /// The order of the key when `--sort-order` is not given.
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortKey::Score | SortKey::Stars | SortKey::LocalScore => SortOrder::Desc,
+            SortKey::LastStar | SortKey::Name => SortOrder::Asc,
+        }
+    }
+}
+
+/// Lists `standings` by `key` in `order`, keeping each member's place and
+/// members on the same value in place order.
+pub fn sort(standings: &mut [Score], aoc: &Aoc, key: SortKey, order: SortOrder) {
+    let names = display_names(&aoc.members);
+    let members: HashMap<&str, (i32, i32, i64)> = aoc
+        .members
+        .values()
+        .map(|member| {
+            let name = names[&member.id].as_str();
+            (
+                name,
+                (member.stars, member.local_score, member.last_star_ts),
+            )
+        })
+        .collect();
+    let value = |score: &Score| members.get(score.member.as_str()).copied();
+    standings.sort_by(|a, b| {
+        let ordering = match key {
+            SortKey::Score => a.points.cmp(&b.points),
+            SortKey::Stars => value(a).map(|v| v.0).cmp(&value(b).map(|v| v.0)),
+            SortKey::LocalScore => value(a).map(|v| v.1).cmp(&value(b).map(|v| v.1)),
+            SortKey::LastStar => value(a).map(|v| v.2).cmp(&value(b).map(|v| v.2)),
+            SortKey::Name => a.member.cmp(&b.member),
+        };
+        match order {
+            SortOrder::Asc => ordering,
+            SortOrder::Desc => ordering.reverse(),
+        }
+    });
+}
+
+/// Orders members on the same points by `tie_breaks`, in turn, and then by
+/// name, and numbers them so that members still tied share a place.
+pub fn break_ties(
+    standings: &mut [Score],
+    aoc: &Aoc,
+    scored: &[(Report, usize)],
+    tie_breaks: &[TieBreak],
+) {
+    let names = display_names(&aoc.members);
+    let mut keys: HashMap<&str, (Reverse<i32>, i64, i64)> = aoc
+        .members
+        .values()
+        .map(|member| {
+            let name = names[&member.id].as_str();
+            (name, (Reverse(member.stars), member.last_star_ts, 0))
+        })
+        .collect();
+    for (event, points) in scored {
+        if let Some(key) = keys.get_mut(&*event.member) {
+            if *points > 0 {
+                key.2 = key.2.max(event.timestamp.timestamp());
+            }
+        }
+    }
+    let compare = |a: &Score, b: &Score| {
+        let (ka, kb) = (keys.get(a.member.as_str()), keys.get(b.member.as_str()));
+        tie_breaks
+            .iter()
+            .map(|tie_break| match (ka, kb, tie_break) {
+                (Some(ka), Some(kb), TieBreak::Stars) => ka.0.cmp(&kb.0),
+                (Some(ka), Some(kb), TieBreak::LastStar) => ka.1.cmp(&kb.1),
+                (Some(ka), Some(kb), TieBreak::FirstToScore) => ka.2.cmp(&kb.2),
+                _ => Ordering::Equal,
+            })
+            .fold(b.points.cmp(&a.points), Ordering::then)
+    };
+    standings.sort_by(|a, b| compare(a, b).then_with(|| a.member.cmp(&b.member)));
+    rank(standings, |a, b| compare(a, b) == Ordering::Equal);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{board, member};
+    use crate::Member;
+
+    /// A member whose star count and last star are all the tie-breaks see.
+    fn with_stars(id: i32, name: &str, stars: i32, last_star_ts: i64) -> Member {
+        Member {
+            stars,
+            last_star_ts,
+            ..member(id, name, &[])
+        }
+    }
+
+    fn score(member: &str, points: usize) -> Score {
+        Score {
+            rank: 0,
+            member: member.to_string(),
+            points,
+            percent: None,
+            local_score: None,
+        }
+    }
+
+    fn places(standings: &[Score]) -> Vec<(usize, &str)> {
+        standings
+            .iter()
+            .map(|score| (score.rank, score.member.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn excluded_days_score_nothing_under_any_scoring() {
+        let aoc = board([
+            member(1, "Alice", &[(1, 1, 100), (2, 1, 200)]),
+            member(2, "Bob", &[(1, 1, 50)]),
+        ]);
+        let counting = Counting {
+            scoring: ScoringSystem::Stars.scoring(),
+            exclude_days: &[1],
+        };
+        let points: Vec<(u32, usize)> = counting
+            .scored(&aoc)
+            .iter()
+            .map(|(event, points)| (event.day(), *points))
+            .collect();
+        assert_eq!(points, [(1, 0), (1, 0), (2, 1)]);
+        let standings = counting.standings(&aoc, &counting.scored(&aoc));
+        assert_eq!(places(&standings), [(1, "Alice"), (2, "Bob")]);
+    }
+
+    #[test]
+    fn day_25_part_2_scores_nothing() {
+        let day_25 = unlock(2024, 25).timestamp();
+        let aoc = board([
+            member(1, "Alice", &[(25, 1, day_25 + 60), (25, 2, day_25 + 61)]),
+            member(2, "Bob", &[(25, 1, day_25 + 120), (25, 2, day_25 + 121)]),
+        ]);
+        for (system, expected) in [
+            (ScoringSystem::Points, [(1, 2), (2, 0), (1, 1), (2, 0)]),
+            (ScoringSystem::Time, [(1, 1439), (2, 0), (1, 1438), (2, 0)]),
+            (ScoringSystem::Stars, [(1, 1), (2, 0), (1, 1), (2, 0)]),
+        ] {
+            let points: Vec<(u32, usize)> = system
+                .scoring()
+                .scored(&aoc)
+                .iter()
+                .map(|(event, points)| (event.part(), *points))
+                .collect();
+            assert_eq!(points, expected, "{system:?}");
+        }
+    }
+
+    #[test]
+    fn ties_are_broken_in_turn_and_then_by_name() {
+        let aoc = board([
+            with_stars(1, "Alice", 4, 300),
+            with_stars(2, "Bob", 4, 200),
+            with_stars(3, "Carol", 6, 400),
+            with_stars(4, "Dave", 4, 200),
+        ]);
+        let mut standings = vec![
+            score("Alice", 10),
+            score("Bob", 10),
+            score("Carol", 10),
+            score("Dave", 10),
+        ];
+        break_ties(&mut standings, &aoc, &[], &[]);
+        assert_eq!(
+            places(&standings),
+            [(1, "Alice"), (1, "Bob"), (1, "Carol"), (1, "Dave")]
+        );
+        break_ties(
+            &mut standings,
+            &aoc,
+            &[],
+            &[TieBreak::Stars, TieBreak::LastStar],
+        );
+        assert_eq!(
+            places(&standings),
+            [(1, "Carol"), (2, "Bob"), (2, "Dave"), (4, "Alice")]
+        );
+    }
+}
@@ -0,0 +1,154 @@
+//! Serves the HTML report and a JSON API over HTTP, refreshing the boards
+//! in the background so viewers need no session cookie.
+use crate::error::{Error, Result};
+use crate::render::LeaderboardReport;
+use crate::scoring::Counting;
+use crate::{html, metrics, Aoc};
+use serde::Serialize;
+use std::sync::RwLock;
+use std::thread::{scope, sleep};
+use std::time::Duration;
+use tiny_http::{Header, Request, Response, Server};
+use tracing::{info, warn};
+
+const HTML: &str = "text/html; charset=utf-8";
+const JSON: &str = "application/json";
+
+/// One entry in `/api/leaderboards`.
+#[derive(Serialize)]
+struct Summary {
+    year: i32,
+    leaderboard: i32,
+    members: usize,
+    stars: i32,
+}
+
+fn json(status: u16, value: &impl Serialize) -> (u16, &'static str, String) {
+    (status, JSON, serde_json::to_string(value).unwrap())
+}
+
+fn not_found(what: &str) -> (u16, &'static str, String) {
+    json(
+        404,
+        &serde_json::json!({ "error": format!("{what} not found") }),
+    )
+}
+
+/// The board with leaderboard `id`, in `year` if given and otherwise the
+/// latest one served.
+fn find(boards: &[Aoc], id: i32, year: Option<i32>) -> Option<&Aoc> {
+    boards
+        .iter()
+        .filter(|aoc| aoc.owner_id == id && year.is_none_or(|year| aoc.year() == year))
+        .max_by_key(|aoc| aoc.year())
+}
+
+/// Routes a request to the HTML report or the JSON API:
+///
+/// - `/api/leaderboards` lists the boards
+/// - `/api/leaderboards/ID/timeline` has the scored events of a board
+/// - `/api/leaderboards/ID/scores` has its standings
+/// - `/metrics` has per-member gauges for Prometheus, with points from
+///   `counting`
+///
+/// Board endpoints take `?year=YYYY` when a leaderboard is served for
+/// several years.
+fn handle(
+    url: &str,
+    boards: &[Aoc],
+    counting: Counting,
+    build: impl Fn(&Aoc) -> Result<LeaderboardReport>,
+) -> (u16, &'static str, String) {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let year = query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("year="))
+        .and_then(|year| year.parse().ok());
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    match segments.as_slice() {
+        ["" | "index.html"] => match boards.iter().map(build).collect::<Result<Vec<_>>>() {
+            Ok(reports) => (200, HTML, html::report(&reports)),
+            Err(e) => (500, HTML, html::escape(&e.to_string())),
+        },
+        ["metrics"] => (
+            200,
+            "text/plain; version=0.0.4; charset=utf-8",
+            metrics::prometheus(boards, counting),
+        ),
+        ["api", "leaderboards"] => json(
+            200,
+            &boards
+                .iter()
+                .map(|aoc| Summary {
+                    year: aoc.year(),
+                    leaderboard: aoc.owner_id,
+                    members: aoc.members.len(),
+                    stars: aoc.members.values().map(|m| m.stars).sum(),
+                })
+                .collect::<Vec<_>>(),
+        ),
+        ["api", "leaderboards", id, view @ ("timeline" | "scores")] => {
+            let Some(aoc) = id.parse().ok().and_then(|id| find(boards, id, year)) else {
+                return not_found(&format!("leaderboard {id}"));
+            };
+            match build(aoc) {
+                Ok(report) if *view == "timeline" => json(200, &report.events),
+                Ok(report) => json(200, &report.standings),
+                Err(e) => json(500, &serde_json::json!({ "error": e.to_string() })),
+            }
+        }
+        _ => not_found(path),
+    }
+}
+
+fn respond(request: Request, status: u16, content_type: &str, body: String) {
+    let header = Header::from_bytes("Content-Type", content_type).unwrap();
+    let response = Response::from_string(body)
+        .with_status_code(status)
+        .with_header(header);
+    if let Err(e) = request.respond(response) {
+        warn!("failed to respond: {e}");
+    }
+}
+
+/// Listens on `addr` until the process is stopped. `load` fetches the
+/// boards again every `refresh` and `build` turns each board into the
+/// report that is served, with `counting` scoring the metrics the same way.
+///
+/// # Errors
+///
+/// When `addr` cannot be listened on.
+pub fn serve(
+    addr: &str,
+    boards: Vec<Aoc>,
+    counting: Counting,
+    refresh: Duration,
+    load: impl FnMut() -> Result<Vec<Aoc>> + Send,
+    build: impl Fn(&Aoc) -> Result<LeaderboardReport>,
+) -> Result<()> {
+    let server = Server::http(addr).map_err(|source| Error::Serve {
+        addr: addr.to_string(),
+        source,
+    })?;
+    info!("serving on http://{}", server.server_addr());
+    let boards = RwLock::new(boards);
+    scope(|s| {
+        s.spawn(|| {
+            let mut load = load;
+            loop {
+                sleep(refresh);
+                match load() {
+                    Ok(fresh) => *boards.write().unwrap() = fresh,
+                    // Keep serving the last good data.
+                    Err(e) => warn!("{e}"),
+                }
+            }
+        });
+        for request in server.incoming_requests() {
+            let (status, content_type, body) =
+                handle(request.url(), &boards.read().unwrap(), counting, &build);
+            respond(request, status, content_type, body);
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,174 @@
+//! A small static website: an index, then a page per leaderboard, day and
+//! member, with the score chart embedded.
+use crate::error::{Error, Result};
+use crate::html::{escape, page};
+use crate::locale;
+use crate::render::{LeaderboardReport, ScoredEvent};
+use crate::scoring::Counting;
+use crate::{duration_string, svg, Aoc};
+use itertools::Itertools;
+use std::fmt::Write as _;
+use std::path::Path;
+
+fn write(path: &Path, contents: &str) -> Result<()> {
+    let error = |source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(error)?;
+    }
+    std::fs::write(path, contents).map_err(error)
+}
+
+fn table(headers: &[&str], rows: impl IntoIterator<Item = Vec<String>>) -> String {
+    let mut html = String::from("<table><thead><tr>");
+    for header in headers {
+        write!(html, "<th>{header}</th>").unwrap();
+    }
+    html.push_str("</tr></thead><tbody>\n");
+    for row in rows {
+        html.push_str("<tr>");
+        for cell in row {
+            write!(html, "<td>{cell}</td>").unwrap();
+        }
+        html.push_str("</tr>\n");
+    }
+    html.push_str("</tbody></table>\n");
+    html
+}
+
+fn directory(report: &LeaderboardReport) -> String {
+    format!("{}-{}", report.year, report.leaderboard)
+}
+
+fn events_table<'a>(events: impl IntoIterator<Item = &'a ScoredEvent>, link: bool) -> String {
+    table(
+        &[
+            "Time",
+            "Member",
+            "Star",
+            "Points",
+            "Elapsed",
+            "Since part 1",
+        ],
+        events.into_iter().map(|ScoredEvent { event, points, .. }| {
+            let member = if link {
+                format!(
+                    "<a href=\"member-{}.html\">{}</a>",
+                    event.member_id,
+                    escape(&event.member)
+                )
+            } else {
+                escape(&event.member)
+            };
+            vec![
+                locale::format(&event.timestamp, "%b %-d %H:%M:%S"),
+                member,
+                event.star.to_string(),
+                points.to_string(),
+                event.elapsed_string(),
+                event.delta.map(duration_string).unwrap_or_default(),
+            ]
+        }),
+    )
+}
+
+fn board_page(aoc: &Aoc, report: &LeaderboardReport, counting: Counting) -> String {
+    let mut body = format!(
+        "<p><a href=\"../index.html\">All leaderboards</a></p>\n<h1>{} leaderboard {}</h1>\n{}",
+        report.year,
+        report.leaderboard,
+        svg::score_chart(aoc, counting)
+    );
+    body.push_str("<h2>Leaderboard</h2>\n");
+    body.push_str(&table(
+        &["#", "Member", "Points"],
+        report.standings.iter().map(|score| {
+            let id = report
+                .events
+                .iter()
+                .find(|e| *e.event.member == *score.member)
+                .map(|e| e.event.member_id);
+            let member = match id {
+                Some(id) => format!("<a href=\"member-{id}.html\">{}</a>", escape(&score.member)),
+                None => escape(&score.member),
+            };
+            vec![score.rank.to_string(), member, score.points.to_string()]
+        }),
+    ));
+    body.push_str("<h2>Days</h2>\n<p>");
+    for day in report.events.iter().map(|e| e.event.day()).sorted().dedup() {
+        write!(body, "<a href=\"day-{day:02}.html\">{day}</a> ").unwrap();
+    }
+    body.push_str("</p>\n");
+    page(
+        &format!("{} leaderboard {}", report.year, report.leaderboard),
+        &body,
+    )
+}
+
+/// Writes the site for `boards` into `out`, replacing pages already there.
+/// `build` turns each board into the report its pages show, and `counting`
+/// scores the charts the same way.
+///
+/// # Errors
+///
+/// When a report cannot be built, or a directory or page cannot be written.
+pub fn write_site(
+    out: &Path,
+    boards: &[Aoc],
+    counting: Counting,
+    build: impl Fn(&Aoc) -> Result<LeaderboardReport>,
+) -> Result<()> {
+    let mut index = String::from("<h1>Advent of Code leaderboards</h1>\n");
+    for aoc in boards {
+        let report = build(aoc)?;
+        let dir = out.join(directory(&report));
+        write(&dir.join("index.html"), &board_page(aoc, &report, counting))?;
+        let by_day = report.events.iter().into_group_map_by(|e| e.event.day());
+        for (day, events) in &by_day {
+            let title = format!("{} day {day}", report.year);
+            let body = format!(
+                "<p><a href=\"index.html\">Leaderboard {}</a></p>\n<h1>{}</h1>\n{}",
+                report.leaderboard,
+                escape(&title),
+                events_table(events.iter().copied(), true)
+            );
+            write(
+                &dir.join(format!("day-{day:02}.html")),
+                &page(&title, &body),
+            )?;
+        }
+        let by_member = report
+            .events
+            .iter()
+            .into_group_map_by(|e| e.event.member_id);
+        for (id, events) in &by_member {
+            let title = format!("{} in {}", events[0].event.member, report.year);
+            let points: usize = events.iter().map(|e| e.points).sum();
+            let body = format!(
+                "<p><a href=\"index.html\">Leaderboard {}</a></p>\n<h1>{}</h1>\n\
+                 <p>{} stars, {points} points</p>\n{}",
+                report.leaderboard,
+                escape(&title),
+                events.len(),
+                events_table(events.iter().copied(), false)
+            );
+            write(&dir.join(format!("member-{id}.html")), &page(&title, &body))?;
+        }
+        write!(
+            index,
+            "<h2><a href=\"{}/index.html\">{} leaderboard {}</a></h2>\n{}",
+            directory(&report),
+            report.year,
+            report.leaderboard,
+            svg::score_chart(aoc, counting)
+        )
+        .unwrap();
+    }
+    write(
+        &out.join("index.html"),
+        &page("Advent of Code leaderboards", &index),
+    )
+}
@@ -0,0 +1,199 @@
+//! One member's solves, typical times and what is still missing.
+use crate::scoring::Counting;
+use crate::streaks::streak;
+use crate::{display_names, duration_string, unlock, Aoc, ElapsedFrom, Member};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A member's stars on one day, part 1 timed from the puzzle unlock and part
+/// 2 from the unlock or from part 1.
+#[derive(Debug, Serialize)]
+pub struct Solve {
+    pub day: u32,
+    #[serde(rename = "part1_seconds", serialize_with = "crate::optional_seconds")]
+    pub part1: Option<Duration>,
+    #[serde(rename = "part2_seconds", serialize_with = "crate::optional_seconds")]
+    pub part2: Option<Duration>,
+    pub points: usize,
+}
+
+/// Average and median of a set of solve times.
+#[derive(Debug, Serialize)]
+pub struct Times {
+    pub solved: usize,
+    #[serde(rename = "average_seconds", serialize_with = "crate::optional_seconds")]
+    pub average: Option<Duration>,
+    #[serde(rename = "median_seconds", serialize_with = "crate::optional_seconds")]
+    pub median: Option<Duration>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberStats {
+    pub year: i32,
+    pub leaderboard: i32,
+    pub member: String,
+    pub member_id: i32,
+    pub stars: i32,
+    pub points: usize,
+    /// Place on the board, shared with members on the same points.
+    pub rank: usize,
+    pub solves: Vec<Solve>,
+    pub part1: Times,
+    pub part2: Times,
+    /// Days with both stars, by the time to the second one.
+    pub best_day: Option<u32>,
+    pub worst_day: Option<u32>,
+    /// Stars that have unlocked but not been earned, like `07-2`.
+    pub missing: Vec<String>,
+    /// Consecutive days with both stars on the day of release.
+    pub longest_streak: usize,
+    pub current_streak: usize,
+}
+
+fn times(mut durations: Vec<Duration>) -> Times {
+    durations.sort();
+    let solved = durations.len();
+    let average = (solved > 0).then(|| {
+        let total: i64 = durations.iter().map(Duration::num_seconds).sum();
+        Duration::seconds(total / i64::try_from(solved).unwrap())
+    });
+    Times {
+        solved,
+        average,
+        median: median(&durations),
+    }
+}
+
+/// The middle of `sorted`, averaging the two middle ones for an even count.
+pub(crate) fn median(sorted: &[Duration]) -> Option<Duration> {
+    let n = sorted.len();
+    match n {
+        0 => None,
+        _ if n % 2 == 1 => Some(sorted[n / 2]),
+        _ => Some(Duration::seconds(i64::midpoint(
+            sorted[n / 2 - 1].num_seconds(),
+            sorted[n / 2].num_seconds(),
+        ))),
+    }
+}
+
+/// Stats for `member` on `aoc`, counting stars that unlocked before `now`
+/// as missing, with part 2 timed `from` the unlock or from part 1 and points
+/// from `counting`.
+pub fn stats(
+    aoc: &Aoc,
+    member: &Member,
+    now: DateTime<Utc>,
+    from: ElapsedFrom,
+    counting: Counting,
+) -> MemberStats {
+    let year = aoc.year();
+    let mut totals: HashMap<i32, usize> = HashMap::new();
+    let mut points: HashMap<(u32, u32), usize> = HashMap::new();
+    for (event, star_points) in counting.scored(aoc) {
+        *totals.entry(event.member_id).or_default() += star_points;
+        if event.member_id == member.id {
+            points.insert((event.day(), event.part()), star_points);
+        }
+    }
+    let total = totals.get(&member.id).copied().unwrap_or(0);
+    let (longest_streak, current_streak) = streak(member, year, now);
+    let elapsed = |day: u32, part: u32| {
+        let star = member.completion_day_level.get(&day)?.get(&part)?;
+        Some(Duration::seconds(
+            star.get_star_ts - unlock(year, day).timestamp(),
+        ))
+    };
+    let unlocked = (1..=25).filter(|day| unlock(year, *day) <= now);
+    let mut solves = Vec::new();
+    let mut missing = Vec::new();
+    for day in unlocked {
+        let (part1, mut part2) = (elapsed(day, 1), elapsed(day, 2));
+        for (part, time) in [(1, part1), (2, part2)] {
+            if time.is_none() {
+                missing.push(format!("{day:02}-{part}"));
+            }
+        }
+        if from == ElapsedFrom::Previous {
+            part2 = part2.zip(part1).map(|(part2, part1)| part2 - part1);
+        }
+        if part1.is_some() || part2.is_some() {
+            solves.push(Solve {
+                day,
+                part1,
+                part2,
+                points: points.get(&(day, 1)).copied().unwrap_or(0)
+                    + points.get(&(day, 2)).copied().unwrap_or(0),
+            });
+        }
+    }
+    let complete = || {
+        solves
+            .iter()
+            .filter_map(|solve| Some((solve.part2?, solve.day)))
+    };
+    MemberStats {
+        year,
+        leaderboard: aoc.owner_id,
+        member: display_names(&aoc.members)[&member.id].clone(),
+        member_id: member.id,
+        stars: member.stars,
+        points: total,
+        rank: 1 + totals.values().filter(|t| **t > total).count(),
+        part1: times(solves.iter().filter_map(|solve| solve.part1).collect()),
+        part2: times(solves.iter().filter_map(|solve| solve.part2).collect()),
+        best_day: complete().min().map(|(_, day)| day),
+        worst_day: complete().max().map(|(_, day)| day),
+        solves,
+        missing,
+        longest_streak,
+        current_streak,
+    }
+}
+
+pub(crate) fn time(d: Option<Duration>) -> String {
+    d.map_or_else(|| "-".to_string(), duration_string)
+}
+
+pub fn print(stats: &MemberStats) {
+    println!(
+        "\n{} on leaderboard {} ({})",
+        stats.member, stats.leaderboard, stats.year
+    );
+    println!(
+        "  {} stars, {} points, place {}",
+        stats.stars, stats.points, stats.rank
+    );
+    println!("\n  Day  Part 1       Part 2       Points");
+    for solve in &stats.solves {
+        println!(
+            "  {:3}  {:11}  {:11}  {:6}",
+            solve.day,
+            time(solve.part1),
+            time(solve.part2),
+            solve.points
+        );
+    }
+    println!();
+    for (part, times) in [(1, &stats.part1), (2, &stats.part2)] {
+        println!(
+            "  Part {part}: {} solved, average {}, median {}",
+            times.solved,
+            time(times.average),
+            time(times.median)
+        );
+    }
+    if let (Some(best), Some(worst)) = (stats.best_day, stats.worst_day) {
+        println!("  Best day {best}, worst day {worst}");
+    }
+    println!(
+        "  Streak {} days, longest {}",
+        stats.current_streak, stats.longest_streak
+    );
+    if stats.missing.is_empty() {
+        println!("  Nothing missing");
+    } else {
+        println!("  Missing: {}", stats.missing.join(", "));
+    }
+}
@@ -0,0 +1,90 @@
+//! Runs of consecutive days with both stars before the next puzzle unlocked.
+use crate::{display_names, unlock, Aoc, Member};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub struct Streak {
+    pub member: String,
+    pub longest: usize,
+    /// Still running at the latest unlocked day. The day still underway
+    /// only counts once it is done.
+    pub current: usize,
+}
+
+/// The longest and the current streak of `member` at `now`.
+pub fn streak(member: &Member, year: i32, now: DateTime<Utc>) -> (usize, usize) {
+    let unlocked: Vec<u32> = (1..=25).filter(|day| unlock(year, *day) <= now).collect();
+    let on_time: Vec<bool> = unlocked
+        .iter()
+        .map(|&day| {
+            member
+                .completion_day_level
+                .get(&day)
+                .and_then(|stars| stars.get(&2))
+                .is_some_and(|star| {
+                    star.get_star_ts < (unlock(year, day) + Duration::days(1)).timestamp()
+                })
+        })
+        .collect();
+    let longest = on_time
+        .split(|done| !done)
+        .map(<[bool]>::len)
+        .max()
+        .unwrap_or(0);
+    let mut days = on_time.iter().rev().peekable();
+    // Today's puzzle can still be done in time.
+    let underway = unlocked
+        .last()
+        .is_some_and(|day| now < unlock(year, *day) + Duration::days(1));
+    if underway {
+        days.next_if(|done| !**done);
+    }
+    let current = days.take_while(|done| **done).count();
+    (longest, current)
+}
+
+/// Streaks of every member with at least one day done in time, longest
+/// current streak first.
+pub fn streaks(aoc: &Aoc, now: DateTime<Utc>) -> Vec<Streak> {
+    let names = display_names(&aoc.members);
+    let mut streaks: Vec<Streak> = aoc
+        .members
+        .values()
+        .map(|member| {
+            let (longest, current) = streak(member, aoc.year(), now);
+            Streak {
+                member: names[&member.id].clone(),
+                longest,
+                current,
+            }
+        })
+        .filter(|streak| streak.longest > 0)
+        .collect();
+    streaks.sort_by(|a, b| {
+        (b.current, b.longest)
+            .cmp(&(a.current, a.longest))
+            .then_with(|| a.member.cmp(&b.member))
+    });
+    streaks
+}
+
+/// `Alice 5 (longest 7), Bob 3` for report footers.
+pub fn summary(streaks: &[Streak], name: impl Fn(&str) -> String) -> String {
+    streaks
+        .iter()
+        .map(|streak| {
+            if streak.longest > streak.current {
+                format!(
+                    "{} {} (longest {})",
+                    name(&streak.member),
+                    streak.current,
+                    streak.longest
+                )
+            } else {
+                format!("{} {}", name(&streak.member), streak.current)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,308 @@
+//! Charts drawn as standalone SVG.
+use crate::error::{Error, Result};
+use crate::html::escape;
+use crate::locale;
+use crate::ranks::ranks;
+use crate::scoring::Counting;
+use crate::{timeline, unlock, Aoc};
+use chrono::{DateTime, Local};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+const WIDTH: f64 = 800.0;
+const HEIGHT: f64 = 400.0;
+const MARGIN: f64 = 40.0;
+const LEGEND: f64 = 160.0;
+const LANE: f64 = 18.0;
+const LABELS: f64 = 150.0;
+const SILVER: &str = "#9999cc";
+const GOLD: &str = "#e6c200";
+const PALETTE: [&str; 10] = [
+    "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+    "#bcbd22", "#17becf",
+];
+
+/// A member's name and running score after each of their stars.
+type Line = (String, Vec<(DateTime<Local>, usize)>);
+
+fn colour(index: usize) -> &'static str {
+    PALETTE[index % PALETTE.len()]
+}
+
+/// Cumulative score per member over the event by the points of `counting`,
+/// one line each.
+#[allow(clippy::cast_precision_loss)]
+pub fn score_chart(aoc: &Aoc, counting: Counting) -> String {
+    let events = counting.scored(aoc);
+    let mut lines: Vec<Line> = Vec::new();
+    let mut index: HashMap<i32, usize> = HashMap::new();
+    for (event, points) in &events {
+        let i = *index.entry(event.member_id).or_insert_with(|| {
+            lines.push((event.member.to_string(), Vec::new()));
+            lines.len() - 1
+        });
+        let total = lines[i].1.last().map_or(0, |(_, total)| *total) + points;
+        lines[i].1.push((event.timestamp, total));
+    }
+    lines.sort_by_key(|(_, points)| std::cmp::Reverse(points.last().map(|(_, t)| *t)));
+    let (Some((first, _)), Some((last, _))) = (events.first(), events.last()) else {
+        return empty();
+    };
+    let start = first.timestamp.timestamp() as f64;
+    let span = (last.timestamp.timestamp() as f64 - start).max(1.0);
+    let max = lines
+        .iter()
+        .filter_map(|(_, points)| points.last().map(|(_, t)| *t))
+        .max()
+        .unwrap_or(0)
+        .max(1) as f64;
+    let plot_width = WIDTH - 2.0 * MARGIN - LEGEND;
+    let plot_height = HEIGHT - 2.0 * MARGIN;
+    let x = |t: DateTime<Local>| MARGIN + (t.timestamp() as f64 - start) / span * plot_width;
+    let y = |score: usize| HEIGHT - MARGIN - score as f64 / max * plot_height;
+    let mut svg = header();
+    axes(&mut svg, plot_width);
+    writeln!(
+        svg,
+        "<text x=\"{MARGIN}\" y=\"{}\" font-size=\"11\">{}</text>\
+         <text x=\"{}\" y=\"{}\" font-size=\"11\" text-anchor=\"end\">{}</text>\
+         <text x=\"{}\" y=\"{}\" font-size=\"11\" text-anchor=\"end\">{max}</text>",
+        HEIGHT - MARGIN + 15.0,
+        locale::format(&first.timestamp, "%b %-d"),
+        MARGIN + plot_width,
+        HEIGHT - MARGIN + 15.0,
+        locale::format(&last.timestamp, "%b %-d"),
+        MARGIN - 4.0,
+        MARGIN + 4.0,
+    )
+    .unwrap();
+    for (i, (member, points)) in lines.iter().enumerate() {
+        let mut path = format!("{:.1},{:.1}", x(points[0].0), y(0));
+        for (timestamp, total) in points {
+            write!(path, " {:.1},{:.1}", x(*timestamp), y(*total)).unwrap();
+        }
+        writeln!(
+            svg,
+            "<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{path}\">\
+             <title>{}</title></polyline>",
+            colour(i),
+            escape(member)
+        )
+        .unwrap();
+        legend(&mut svg, i, member);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn header() -> String {
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{HEIGHT}\" \
+         viewBox=\"0 0 {WIDTH} {HEIGHT}\" font-family=\"sans-serif\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n"
+    )
+}
+
+fn axes(svg: &mut String, plot_width: f64) {
+    writeln!(
+        svg,
+        "<path d=\"M{MARGIN},{MARGIN} V{} H{}\" fill=\"none\" stroke=\"#999\"/>",
+        HEIGHT - MARGIN,
+        MARGIN + plot_width
+    )
+    .unwrap();
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn legend(svg: &mut String, index: usize, member: &str) {
+    let y = MARGIN + index as f64 * 16.0;
+    let x = WIDTH - LEGEND;
+    writeln!(
+        svg,
+        "<rect x=\"{x}\" y=\"{}\" width=\"10\" height=\"10\" fill=\"{}\"/>\
+         <text x=\"{}\" y=\"{y}\" font-size=\"11\">{}</text>",
+        y - 9.0,
+        colour(index),
+        x + 14.0,
+        escape(member)
+    )
+    .unwrap();
+}
+
+fn empty() -> String {
+    let mut svg = header();
+    writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\">No stars yet</text>\n</svg>",
+        WIDTH / 2.0,
+        HEIGHT / 2.0
+    )
+    .unwrap();
+    svg
+}
+
+/// Every member's stars on a time axis across the month, one lane per
+/// member, with the boards stacked.
+#[allow(clippy::cast_precision_loss)]
+pub fn gantt(boards: &[Aoc]) -> String {
+    let plot_width = WIDTH - LABELS - MARGIN;
+    let mut body = String::new();
+    let mut top = 0.0;
+    for aoc in boards {
+        let year = aoc.year();
+        let start = unlock(year, 1).timestamp() as f64;
+        let span = (unlock(year, 25).timestamp() + 86_400) as f64 - start;
+        let x = |timestamp: i64| LABELS + (timestamp as f64 - start) / span * plot_width;
+        let events = timeline(aoc);
+        let mut lanes: Vec<(i32, &str)> = Vec::new();
+        for event in &events {
+            if !lanes.iter().any(|(id, _)| *id == event.member_id) {
+                lanes.push((event.member_id, &event.member));
+            }
+        }
+        writeln!(
+            body,
+            "<text x=\"{MARGIN}\" y=\"{}\" font-weight=\"bold\">{year} leaderboard {}</text>",
+            top + 24.0,
+            aoc.owner_id
+        )
+        .unwrap();
+        let lanes_top = top + MARGIN;
+        let lanes_bottom = lanes_top + LANE * lanes.len() as f64;
+        for day in 1..=25 {
+            let day_x = x(unlock(year, day).timestamp());
+            writeln!(
+                body,
+                "<line x1=\"{day_x:.1}\" y1=\"{lanes_top}\" x2=\"{day_x:.1}\" y2=\"{lanes_bottom}\" \
+                 stroke=\"#ddd\"/><text x=\"{day_x:.1}\" y=\"{}\" font-size=\"9\">{day}</text>",
+                lanes_top - 4.0
+            )
+            .unwrap();
+        }
+        for (lane, (id, member)) in lanes.iter().enumerate() {
+            let y = lanes_top + LANE * lane as f64 + LANE / 2.0;
+            writeln!(
+                body,
+                "<text x=\"{}\" y=\"{}\" font-size=\"11\" text-anchor=\"end\">{}</text>",
+                LABELS - 6.0,
+                y + 4.0,
+                escape(member)
+            )
+            .unwrap();
+            for event in events.iter().filter(|e| e.member_id == *id) {
+                let star_x = x(event.timestamp.timestamp());
+                if event.part() == 2 {
+                    let from = x((event.timestamp - event.elapsed).timestamp());
+                    writeln!(
+                        body,
+                        "<line x1=\"{from:.1}\" y1=\"{y}\" x2=\"{star_x:.1}\" y2=\"{y}\" \
+                         stroke=\"{GOLD}\" stroke-width=\"3\"/>"
+                    )
+                    .unwrap();
+                }
+                writeln!(
+                    body,
+                    "<circle cx=\"{star_x:.1}\" cy=\"{y}\" r=\"3.5\" fill=\"{}\">\
+                     <title>{} {} {}</title></circle>",
+                    if event.part() == 1 { SILVER } else { GOLD },
+                    escape(member),
+                    event.star,
+                    locale::format(&event.timestamp, "%b %-d %H:%M:%S")
+                )
+                .unwrap();
+            }
+        }
+        top = lanes_bottom + MARGIN / 2.0;
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{top}\" \
+         viewBox=\"0 0 {WIDTH} {top}\" font-family=\"sans-serif\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n{body}</svg>\n"
+    )
+}
+
+/// Every member's place after each day, one line per member with the names
+/// at their final place, with the boards stacked. Places go by the points of
+/// `counting`.
+#[allow(clippy::cast_precision_loss)]
+pub fn bump_chart(boards: &[Aoc], counting: Counting) -> String {
+    let plot_width = WIDTH - MARGIN - LABELS;
+    let mut body = String::new();
+    let mut top = 0.0;
+    for aoc in boards {
+        let rows = ranks(aoc, counting);
+        let days = rows.first().map_or(0, |row| row.ranks.len());
+        let step = plot_width / days.saturating_sub(1).max(1) as f64;
+        let x = |day: usize| MARGIN + day as f64 * step;
+        let plot_top = top + MARGIN;
+        let y = |rank: usize| plot_top + LANE * (rank as f64 - 0.5);
+        let plot_bottom = plot_top + LANE * rows.len() as f64;
+        writeln!(
+            body,
+            "<text x=\"{MARGIN}\" y=\"{}\" font-weight=\"bold\">{} leaderboard {}</text>",
+            top + 24.0,
+            aoc.year(),
+            aoc.owner_id
+        )
+        .unwrap();
+        for day in 0..days {
+            writeln!(
+                body,
+                "<line x1=\"{0:.1}\" y1=\"{plot_top}\" x2=\"{0:.1}\" y2=\"{plot_bottom}\" \
+                 stroke=\"#ddd\"/><text x=\"{0:.1}\" y=\"{1}\" font-size=\"9\" \
+                 text-anchor=\"middle\">{2}</text>",
+                x(day),
+                plot_bottom + 12.0,
+                day + 1
+            )
+            .unwrap();
+        }
+        for (i, row) in rows.iter().enumerate() {
+            let points: Vec<String> = row
+                .ranks
+                .iter()
+                .enumerate()
+                .filter_map(|(day, rank)| Some(format!("{:.1},{:.1}", x(day), y((*rank)?))))
+                .collect();
+            writeln!(
+                body,
+                "<polyline fill=\"none\" stroke=\"{0}\" stroke-width=\"2\" points=\"{1}\">\
+                 <title>{2}</title></polyline>\
+                 <text x=\"{3:.1}\" y=\"{4:.1}\" font-size=\"11\" fill=\"{0}\">{2}</text>",
+                colour(i),
+                points.join(" "),
+                escape(&row.member),
+                x(days.saturating_sub(1)) + 6.0,
+                plot_top + LANE * i as f64 + LANE / 2.0 + 4.0,
+            )
+            .unwrap();
+        }
+        top = plot_bottom + MARGIN / 2.0;
+    }
+    format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{WIDTH}\" height=\"{top}\" \
+         viewBox=\"0 0 {WIDTH} {top}\" font-family=\"sans-serif\">\n\
+         <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n{body}</svg>\n"
+    )
+}
+
+/// # Errors
+///
+/// When the file cannot be written.
+pub fn write_bump_chart(path: &Path, boards: &[Aoc], counting: Counting) -> Result<()> {
+    std::fs::write(path, bump_chart(boards, counting)).map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// # Errors
+///
+/// When the file cannot be written.
+pub fn write_gantt(path: &Path, boards: &[Aoc]) -> Result<()> {
+    std::fs::write(path, gantt(boards)).map_err(|source| Error::Write {
+        path: path.to_path_buf(),
+        source,
+    })
+}
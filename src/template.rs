@@ -0,0 +1,92 @@
+//! Reports rendered through a user's Handlebars template.
+//!
+//! Templates see the document `--format json` prints. In a template whose
+//! file name ends in `.html` or `.htm` values are HTML-escaped and
+//! `{{{path.to.value}}}` inserts one as it is; other templates, like chat
+//! messages, insert every value as it is.
+use crate::error::{Error, Result};
+use crate::render::Document;
+use handlebars::{no_escape, Handlebars};
+use serde::Serialize;
+use std::path::Path;
+
+/// Whether the template at `path` makes HTML, so its values are escaped.
+fn is_html(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm"))
+}
+
+/// Renders `document` through the template at `path`.
+///
+/// # Errors
+///
+/// When the template cannot be read or has a mistake, like a block that is
+/// never closed.
+pub fn render(path: &Path, document: &Document) -> Result<String> {
+    let error = |message: String| Error::Template {
+        path: path.to_path_buf(),
+        message,
+    };
+    let template = std::fs::read_to_string(path).map_err(|e| error(e.to_string()))?;
+    fill(&template, document, is_html(path)).map_err(error)
+}
+
+/// Renders `template` with `data`, escaping values for HTML if `html`.
+fn fill(template: &str, data: &impl Serialize, html: bool) -> std::result::Result<String, String> {
+    let mut handlebars = Handlebars::new();
+    if !html {
+        handlebars.register_escape_fn(no_escape);
+    }
+    handlebars
+        .render_template(template, data)
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn data() -> Value {
+        json!({
+            "year": 2024,
+            "standings": [
+                {"member": "Alice", "points": 12, "badges": []},
+                {"member": "<b>Bob</b>", "points": 0, "badges": ["Early bird"]},
+            ],
+        })
+    }
+
+    #[test]
+    fn loops_and_conditions() {
+        let template = "{{year}}:{{#each standings}} {{@index}}.{{member}}\
+            {{#if points}}={{this.points}}{{else}}-{{/if}}\
+            {{#unless @last}},{{/unless}}{{/each}}";
+        assert_eq!(
+            fill(template, &data(), false).unwrap(),
+            "2024: 0.Alice=12, 1.<b>Bob</b>-"
+        );
+    }
+
+    #[test]
+    fn html_templates_escape_values() {
+        let template = "{{#each standings}}{{member}} {{{member}}};{{/each}}";
+        assert_eq!(
+            fill(template, &data(), true).unwrap(),
+            "Alice Alice;&lt;b&gt;Bob&lt;/b&gt; <b>Bob</b>;"
+        );
+        assert!(is_html(Path::new("report.HTML")));
+        assert!(!is_html(Path::new("slack.txt")));
+    }
+
+    #[test]
+    fn mistakes_are_reported() {
+        assert!(fill("{{#each standings}}", &data(), false).is_err());
+        assert!(fill("{{#if year}}{{/each}}", &data(), false).is_err());
+        assert!(fill("{{year", &data(), false).is_err());
+        assert_eq!(
+            fill("{{! note }}{{missing}}!", &data(), false).unwrap(),
+            "!"
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! Keeps to adventofcode.com's wish that a leaderboard is requested at most
+//! once every 15 minutes, across runs and whatever happens to the cache.
+use crate::config::Config;
+use crate::error::{Error, Result};
+use crate::warnings::{Category, Warnings};
+use crate::{atomic, Board};
+use chrono::{DateTime, Duration, Local};
+use std::collections::HashMap;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+/// Minutes between two requests for the same leaderboard.
+pub const MINUTES: i64 = 15;
+
+/// When each leaderboard was last requested, saved next to the cache as
+/// `<cache stem>.throttle.json`.
+pub struct Throttle {
+    path: PathBuf,
+    /// Keyed by `year/leaderboard`.
+    requested: HashMap<String, DateTime<Local>>,
+}
+
+impl Throttle {
+    /// A file that cannot be parsed, as after a crash while writing it, is
+    /// set aside as `<file>.corrupt` with a warning in `warnings`, and every
+    /// board may be requested at once.
+    ///
+    /// # Errors
+    ///
+    /// When the file exists but cannot be read.
+    pub fn load(config: &Config, warnings: &mut Warnings) -> Result<Throttle> {
+        let path = config.cache.with_extension("throttle.json");
+        if !path.exists() {
+            return Ok(Throttle {
+                path,
+                requested: HashMap::new(),
+            });
+        }
+        let text = read_to_string(&path).map_err(|source| Error::CacheRead {
+            path: path.clone(),
+            source,
+        })?;
+        let requested = serde_json::from_str(&text).unwrap_or_else(|e| {
+            let mut aside = path.clone().into_os_string();
+            aside.push(".corrupt");
+            warnings.push(
+                Category::Cache,
+                format!(
+                    "{} is not JSON ({e}), starting afresh; the old file is kept as {}",
+                    path.display(),
+                    Path::new(&aside).display()
+                ),
+            );
+            let _ = std::fs::rename(&path, &aside);
+            HashMap::new()
+        });
+        Ok(Throttle { path, requested })
+    }
+
+    /// When `board` may be requested again, or `None` if it may be now.
+    pub fn until(&self, board: Board) -> Option<DateTime<Local>> {
+        let next = *self.requested.get(&board.cache_key())? + Duration::minutes(MINUTES);
+        (next > Local::now()).then_some(next)
+    }
+
+    /// Notes that `boards` are requested now, before they are.
+    ///
+    /// # Errors
+    ///
+    /// When the file cannot be written.
+    pub fn record(&mut self, boards: &[Board]) -> Result<()> {
+        let now = Local::now();
+        for board in boards {
+            self.requested.insert(board.cache_key(), now);
+        }
+        atomic::write(
+            &self.path,
+            serde_json::to_string(&self.requested).unwrap().as_bytes(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_truncated_file_is_set_aside() {
+        let dir = std::env::temp_dir().join(format!("aoc-throttle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = Config {
+            cache: dir.join("cache.json"),
+            ..Config::default()
+        };
+        let path = dir.join("cache.throttle.json");
+        std::fs::write(&path, "{\"2024/1\": \"2024-12-").unwrap();
+        let mut warnings = Warnings::default();
+        let throttle = Throttle::load(&config, &mut warnings).unwrap();
+        assert!(throttle.requested.is_empty());
+        assert_eq!(warnings.collected()[0].category, Category::Cache);
+        assert!(!path.exists());
+        assert!(dir.join("cache.throttle.json.corrupt").exists());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
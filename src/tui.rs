@@ -0,0 +1,253 @@
+//! An interactive terminal view: a tab per leaderboard with the timeline
+//! and standings side by side, filters by day and member, and refresh on
+//! demand.
+use crate::error::{Error, Result};
+use crate::locale;
+use crate::render::{LeaderboardReport, ScoredEvent};
+use crate::{duration_string, Aoc};
+use itertools::Itertools;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph, Row, Table, TableState, Tabs};
+use ratatui::{DefaultTerminal, Frame};
+
+const HELP: &str = "←/→ board  ↑/↓ PgUp/PgDn scroll  d day  m member  c clear  r refresh  q quit";
+
+struct App {
+    reports: Vec<LeaderboardReport>,
+    tab: usize,
+    timeline: TableState,
+    day: Option<u32>,
+    member: Option<String>,
+    status: String,
+}
+
+/// What `build` makes of each board.
+type Build<'a> = dyn Fn(&Aoc) -> Result<LeaderboardReport> + 'a;
+
+/// The value after `current` in `values`, or none after the last one.
+fn cycle<T: Clone + PartialEq>(values: &[T], current: Option<&T>) -> Option<T> {
+    match current {
+        None => values.first().cloned(),
+        Some(current) => values
+            .iter()
+            .position(|value| value == current)
+            .and_then(|i| values.get(i + 1))
+            .cloned(),
+    }
+}
+
+impl App {
+    fn report(&self) -> Option<&LeaderboardReport> {
+        self.reports.get(self.tab)
+    }
+
+    fn events(&self) -> Vec<&ScoredEvent> {
+        self.report().map_or_else(Vec::new, |report| {
+            report
+                .events
+                .iter()
+                .filter(|e| self.day.is_none_or(|day| e.event.day() == day))
+                .filter(|e| self.member.as_ref().is_none_or(|m| *e.event.member == **m))
+                .collect()
+        })
+    }
+
+    fn next_day(&mut self) {
+        let days: Vec<u32> = self.report().map_or_else(Vec::new, |report| {
+            report
+                .events
+                .iter()
+                .map(|e| e.event.day())
+                .sorted()
+                .dedup()
+                .collect()
+        });
+        self.day = cycle(&days, self.day.as_ref());
+        self.timeline.select_first();
+    }
+
+    fn next_member(&mut self) {
+        let members: Vec<String> = self.report().map_or_else(Vec::new, |report| {
+            report.standings.iter().map(|s| s.member.clone()).collect()
+        });
+        self.member = cycle(&members, self.member.as_ref());
+        self.timeline.select_first();
+    }
+
+    fn switch(&mut self, tab: usize) {
+        self.tab = tab;
+        self.day = None;
+        self.member = None;
+        self.timeline.select_first();
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [tabs, body, help] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let titles = self
+            .reports
+            .iter()
+            .map(|r| format!("{} {}", r.year, r.leaderboard));
+        frame.render_widget(
+            Tabs::new(titles)
+                .select(self.tab)
+                .block(Block::bordered().title("Leaderboards"))
+                .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+            tabs,
+        );
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(body);
+        let mut filters = Vec::new();
+        if let Some(day) = self.day {
+            filters.push(format!("day {day}"));
+        }
+        if let Some(member) = &self.member {
+            filters.push(member.clone());
+        }
+        let title = if filters.is_empty() {
+            "Timeline".to_string()
+        } else {
+            format!("Timeline ({})", filters.join(", "))
+        };
+        let rows: Vec<Row> = self
+            .events()
+            .into_iter()
+            .map(|ScoredEvent { event, points, .. }| {
+                Row::new([
+                    locale::format(&event.timestamp, "%b %e %H:%M:%S"),
+                    event.member.to_string(),
+                    event.star.to_string(),
+                    points.to_string(),
+                    event.elapsed_string(),
+                    event.delta.map(duration_string).unwrap_or_default(),
+                ])
+            })
+            .collect();
+        let timeline = Table::new(
+            rows,
+            [
+                Constraint::Length(15),
+                Constraint::Min(12),
+                Constraint::Length(5),
+                Constraint::Length(4),
+                Constraint::Length(12),
+                Constraint::Length(12),
+            ],
+        )
+        .header(Row::new(["Time", "Member", "Star", "Pts", "Elapsed", "Since p1"]).bold())
+        .block(Block::bordered().title(title))
+        .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(timeline, left, &mut self.timeline);
+        let standings: Vec<Row> = self.report().map_or_else(Vec::new, |report| {
+            report
+                .standings
+                .iter()
+                .map(|score| {
+                    Row::new([
+                        format!("{}.", score.rank),
+                        score.member.clone(),
+                        score.points.to_string(),
+                    ])
+                })
+                .collect()
+        });
+        frame.render_widget(
+            Table::new(
+                standings,
+                [
+                    Constraint::Length(4),
+                    Constraint::Min(12),
+                    Constraint::Length(6),
+                ],
+            )
+            .block(Block::bordered().title("Leaderboard")),
+            right,
+        );
+        let status = if self.status.is_empty() {
+            HELP.to_string()
+        } else {
+            format!("{}  |  {HELP}", self.status)
+        };
+        frame.render_widget(Paragraph::new(Line::from(status)), help);
+    }
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    app: &mut App,
+    refresh: &mut dyn FnMut() -> Result<Vec<Aoc>>,
+    build: &Build,
+) -> std::io::Result<()> {
+    loop {
+        terminal.draw(|frame| app.draw(frame))?;
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let tabs = app.reports.len().max(1);
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Right | KeyCode::Tab => app.switch((app.tab + 1) % tabs),
+            KeyCode::Left | KeyCode::BackTab => app.switch((app.tab + tabs - 1) % tabs),
+            KeyCode::Down | KeyCode::Char('j') => app.timeline.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => app.timeline.select_previous(),
+            KeyCode::PageDown => app.timeline.scroll_down_by(20),
+            KeyCode::PageUp => app.timeline.scroll_up_by(20),
+            KeyCode::Home | KeyCode::Char('g') => app.timeline.select_first(),
+            KeyCode::End | KeyCode::Char('G') => app.timeline.select_last(),
+            KeyCode::Char('d') => app.next_day(),
+            KeyCode::Char('m') => app.next_member(),
+            KeyCode::Char('c') => app.switch(app.tab),
+            KeyCode::Char('r') => {
+                let reports = refresh().and_then(|boards| boards.iter().map(build).collect());
+                app.status = match reports {
+                    Ok(reports) => {
+                        app.reports = reports;
+                        app.tab = app.tab.min(app.reports.len().saturating_sub(1));
+                        format!("refreshed at {}", chrono::Local::now().format("%H:%M:%S"))
+                    }
+                    Err(e) => format!("refresh failed: {e}"),
+                };
+                // Fetching reports progress on stderr, over the screen.
+                terminal.clear()?;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Runs until the user quits. `refresh` loads the boards again when `r` is
+/// pressed, and `build` makes the report shown for each board.
+///
+/// # Errors
+///
+/// When a report cannot be built or the terminal cannot be set up or read
+/// from.
+pub fn run(
+    boards: &[Aoc],
+    mut refresh: impl FnMut() -> Result<Vec<Aoc>>,
+    build: impl Fn(&Aoc) -> Result<LeaderboardReport>,
+) -> Result<()> {
+    let mut app = App {
+        reports: boards.iter().map(&build).collect::<Result<_>>()?,
+        tab: 0,
+        timeline: TableState::new().with_selected(0),
+        day: None,
+        member: None,
+        status: String::new(),
+    };
+    let mut terminal = ratatui::try_init().map_err(Error::Terminal)?;
+    let result = event_loop(&mut terminal, &mut app, &mut refresh, &build);
+    ratatui::restore();
+    result.map_err(Error::Terminal)
+}
@@ -0,0 +1,72 @@
+//! Warnings collected during a run and printed together at the end.
+use itertools::Itertools;
+use serde::Serialize;
+use std::fmt;
+use tracing::warn;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Cache,
+    Fetch,
+    Members,
+    Notify,
+    Times,
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Category::Cache => "cache",
+            Category::Fetch => "fetch",
+            Category::Members => "members",
+            Category::Notify => "notify",
+            Category::Times => "times",
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct Warning {
+    pub category: Category,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+pub struct Warnings(Vec<Warning>);
+
+impl Warnings {
+    pub fn push(&mut self, category: Category, message: impl Into<String>) {
+        self.0.push(Warning {
+            category,
+            message: message.into(),
+        });
+    }
+
+    /// Deduplicated and ordered by category.
+    pub fn collected(&self) -> Vec<Warning> {
+        self.0.iter().sorted().dedup().cloned().collect()
+    }
+
+    /// Prints the warnings footer to stderr, if there is anything to say.
+    pub fn print(&self) {
+        let warnings = self.collected();
+        if warnings.is_empty() {
+            return;
+        }
+        eprintln!("\nWarnings:");
+        for (category, group) in &warnings.iter().group_by(|w| w.category) {
+            for warning in group {
+                eprintln!("  [{category}] {}", warning.message);
+            }
+        }
+    }
+
+    /// Logs the warnings one by one, for modes that keep running and have
+    /// no footer.
+    pub fn log(&self) {
+        for warning in self.collected() {
+            warn!("[{}] {}", warning.category, warning.message);
+        }
+    }
+}
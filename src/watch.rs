@@ -0,0 +1,145 @@
+//! Keep running and report changes as they happen.
+use crate::config::Config;
+use crate::diff::{self, DiffFormat};
+use crate::error::Result;
+use crate::fetch::get_all;
+use crate::locale;
+use crate::render::StarKeys;
+use crate::scoring::Counting;
+use crate::warnings::Warnings;
+use crate::{filter, notify, throttle, timeline, Aoc, Board};
+use chrono::Local;
+use std::io::IsTerminal;
+use std::thread::sleep;
+use std::time::Duration;
+use tracing::warn;
+
+/// The shortest cache lifetime of `targets`, as polling more often would
+/// only hit the cache.
+pub fn poll(config: &Config, targets: &[Board]) -> Duration {
+    let minutes = targets
+        .iter()
+        .map(|board| config.cache_ttl(board.leaderboard))
+        .min()
+        .unwrap_or(throttle::MINUTES)
+        .max(throttle::MINUTES);
+    Duration::from_mins(minutes.unsigned_abs())
+}
+
+/// Polls the leaderboards as often as the cache allows, printing a timestamped ticker of new
+/// stars and rank moves instead of the whole report, and the round's
+/// warnings, with points from `counting`. `rename` gives fresh boards the
+/// same names as `boards`.
+pub fn diff_only(
+    config: &Config,
+    targets: &[Board],
+    counting: Counting,
+    mut boards: Vec<Aoc>,
+    rename: impl Fn(&mut [Aoc]),
+) -> ! {
+    let interval = poll(config, targets);
+    loop {
+        sleep(interval);
+        let mut warnings = Warnings::default();
+        let mut fresh = match get_all(config, targets, &mut warnings) {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                // Try again next round rather than ending the watch.
+                warn!("{e}");
+                continue;
+            }
+        };
+        for aoc in &mut fresh {
+            filter::restrict(aoc, &config.members);
+        }
+        rename(&mut fresh);
+        let diffs: Vec<_> = targets
+            .iter()
+            .zip(boards.iter().zip(&fresh))
+            .map(|(board, (old, new))| diff::diff(*board, old, new, counting))
+            .filter(|diff| !diff.is_empty())
+            .collect();
+        notify::send(config, &diffs, &mut warnings);
+        if !diffs.is_empty() {
+            println!(
+                "\n--- {} ---",
+                locale::format(&Local::now(), "%B %e %H:%M:%S")
+            );
+            diff::print(&diffs, DiffFormat::Text);
+        }
+        warnings.print();
+        boards = fresh;
+    }
+}
+
+fn stars(boards: &[Aoc]) -> StarKeys {
+    boards
+        .iter()
+        .flat_map(|aoc| {
+            let year = aoc.year();
+            timeline(aoc)
+                .into_iter()
+                .map(move |event| (year, event.member_id, event.star))
+        })
+        .collect()
+}
+
+/// Loads the boards again every `interval` and hands them to `show` along
+/// with the stars that were not there the round before and the warnings of
+/// loading them, clearing the terminal in between.
+pub fn redraw(
+    interval: Duration,
+    boards: &[Aoc],
+    mut load: impl FnMut(&mut Warnings) -> Result<Vec<Aoc>>,
+    mut show: impl FnMut(&[Aoc], &StarKeys, &mut Warnings) -> Result<()>,
+) -> ! {
+    let mut seen = stars(boards);
+    loop {
+        sleep(interval);
+        let mut warnings = Warnings::default();
+        let fresh = match load(&mut warnings) {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                warn!("{e}");
+                continue;
+            }
+        };
+        let current = stars(&fresh);
+        let new = current.difference(&seen).copied().collect();
+        if std::io::stdout().is_terminal() {
+            print!("\x1b[2J\x1b[H");
+        }
+        if let Err(e) = show(&fresh, &new, &mut warnings) {
+            warn!("{e}");
+        }
+        seen = current;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn polls_as_often_as_the_shortest_cache_lifetime() {
+        let board = |leaderboard| Board {
+            year: 2024,
+            leaderboard,
+        };
+        let config = Config {
+            cache_ttl: Some(60),
+            leaderboard_cache_ttl: HashMap::from([("2".to_string(), 30)]),
+            ..Config::default()
+        };
+        assert_eq!(poll(&config, &[board(1)]), Duration::from_hours(1));
+        assert_eq!(
+            poll(&config, &[board(1), board(2)]),
+            Duration::from_mins(30)
+        );
+        assert_eq!(
+            poll(&Config::default(), &[board(1)]),
+            Duration::from_mins(15)
+        );
+    }
+}